@@ -1,10 +1,10 @@
 use ethereum_mysql::{sqladdress, SqlAddress};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, MySqlPool};
-use typed_sqlx_client::{CrudOpsRef, SelectOnlyQuery, SqlPool};
+use typed_sqlx_client::{CrudOpsRef, SelectOnlyQuery, SqlPool, TableDdl};
 use uuid::Uuid;
 
-#[derive(FromRow, CrudOpsRef, Clone, Debug, Serialize, Deserialize)]
+#[derive(FromRow, CrudOpsRef, TableDdl, Clone, Debug, Serialize, Deserialize)]
 #[crud(table = "user_infos", db = "mysql")]
 pub struct UserInfo {
     #[crud(primary_key)]
@@ -13,7 +13,7 @@ pub struct UserInfo {
     pub email: String,
     pub age: Option<i32>,
     pub is_active: bool,
-    #[crud(rename = "address")]
+    #[crud(rename = "address", sql_type = "VARCHAR(42)")]
     #[sqlx(rename = "address")]
     pub user_address: SqlAddress,
 }
@@ -26,23 +26,8 @@ async fn main() {
     let pool = SqlPool::from_pool::<TestDB>(MySqlPool::connect(&database_url).await.unwrap());
     let user_info_table = pool.get_table::<UserInfo>();
     // Drop table if exists to ensure a fresh table each time
-    let _ = sqlx::query("DROP TABLE IF EXISTS user_infos")
-        .execute(user_info_table.get_pool())
-        .await
-        .unwrap();
-    let _ = sqlx::query(
-        "CREATE TABLE IF NOT EXISTS user_infos (
-            id BINARY(16) PRIMARY KEY,
-            name VARCHAR(25) NOT NULL,
-            email VARCHAR(255) NOT NULL,
-            age INT,
-            is_active BOOLEAN NOT NULL,
-            address VARCHAR(42) NOT NULL
-        )",
-    )
-    .execute(user_info_table.get_pool())
-    .await
-    .unwrap();
+    user_info_table.drop_table().await.unwrap();
+    user_info_table.create_table_if_not_exists().await.unwrap();
 
     let user_info = UserInfo {
         id: Some(Uuid::new_v4()),
@@ -1,10 +1,10 @@
 use ethereum_mysql::{sqladdress, SqlAddress};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, PgPool};
-use typed_sqlx_client::{CrudOpsRef, SelectOnlyQuery, SqlPool};
+use typed_sqlx_client::{CrudOpsRef, SelectOnlyQuery, SqlPool, TableDdl};
 use uuid::Uuid;
 
-#[derive(FromRow, CrudOpsRef, Clone, Debug, Serialize, Deserialize)]
+#[derive(FromRow, CrudOpsRef, TableDdl, Clone, Debug, Serialize, Deserialize)]
 #[crud(table = "user_infos", db = "postgres")]
 pub struct UserInfo {
     #[crud(primary_key)]
@@ -13,7 +13,7 @@ pub struct UserInfo {
     pub email: String,
     pub age: Option<i32>,
     pub is_active: bool,
-    #[crud(rename = "address")]
+    #[crud(rename = "address", sql_type = "VARCHAR(42)")]
     #[sqlx(rename = "address")]
     pub user_address: SqlAddress,
 }
@@ -27,23 +27,8 @@ async fn main() {
     let pool = SqlPool::from_pool::<TestDB>(pool);
     let user_info_table = pool.get_table::<UserInfo>();
     // Drop table if exists to ensure a fresh table each time
-    let _ = sqlx::query("DROP TABLE IF EXISTS user_infos")
-        .execute(user_info_table.get_pool())
-        .await
-        .unwrap();
-    let _ = sqlx::query(
-        "CREATE TABLE IF NOT EXISTS user_infos (
-            id UUID PRIMARY KEY,
-            name VARCHAR(25) NOT NULL,
-            email VARCHAR(255) NOT NULL,
-            age INT,
-            is_active BOOLEAN NOT NULL,
-            address VARCHAR(42) NOT NULL
-        )",
-    )
-    .execute(user_info_table.get_pool())
-    .await
-    .unwrap();
+    user_info_table.drop_table().await.unwrap();
+    user_info_table.create_table_if_not_exists().await.unwrap();
 
     let user_info = UserInfo {
         id: Some(Uuid::new_v4()),
@@ -62,7 +47,7 @@ async fn main() {
     assert!(query.len() == 1, "Expected one user info record");
     let into_of_value = query.into_iter().next().unwrap();
     println!("Query result: {:?}", into_of_value);
-    let uuid = into_of_value.id.as_ref().unwrap().clone();
+    let uuid = into_of_value.id.unwrap();
     let mut user_info = user_info_table.get_by_id(&uuid).await.unwrap().unwrap();
     assert!(
         user_info.age == Some(30),
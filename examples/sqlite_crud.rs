@@ -1,10 +1,10 @@
 use ethereum_mysql::{sqladdress, SqlAddress};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
-use typed_sqlx_client::{CrudOpsRef, SelectOnlyQuery, SqlPool};
+use typed_sqlx_client::{CrudOpsRef, SelectOnlyQuery, SqlPool, TableDdl};
 use uuid::Uuid;
 
-#[derive(FromRow, CrudOpsRef, Clone, Debug, Serialize, Deserialize)]
+#[derive(FromRow, CrudOpsRef, TableDdl, Clone, Debug, Serialize, Deserialize)]
 #[crud(table = "user_infos", db = "sqlite")]
 pub struct UserInfo {
     #[crud(primary_key)]
@@ -13,7 +13,7 @@ pub struct UserInfo {
     pub email: String,
     pub age: Option<i32>,
     pub is_active: bool,
-    #[crud(rename = "address")]
+    #[crud(rename = "address", sql_type = "VARCHAR(42)")]
     #[sqlx(rename = "address")]
     pub user_address: SqlAddress,
 }
@@ -27,23 +27,8 @@ async fn main() {
     let pool = SqlPool::from_pool::<TestDB>(pool);
     let user_info_table = pool.get_table::<UserInfo>();
     // Drop table if exists to ensure a fresh table each time
-    let _ = sqlx::query("DROP TABLE IF EXISTS user_infos")
-        .execute(user_info_table.get_pool())
-        .await
-        .unwrap();
-    let _ = sqlx::query(
-        "CREATE TABLE IF NOT EXISTS user_infos (
-            id BINARY(16) PRIMARY KEY,
-            name VARCHAR(25) NOT NULL,
-            email VARCHAR(255) NOT NULL,
-            age INT,
-            is_active BOOLEAN NOT NULL,
-            address VARCHAR(42) NOT NULL
-        )",
-    )
-    .execute(user_info_table.get_pool())
-    .await
-    .unwrap();
+    user_info_table.drop_table().await.unwrap();
+    user_info_table.create_table_if_not_exists().await.unwrap();
 
     let user_info = UserInfo {
         id: Some(Uuid::new_v4()),
@@ -103,6 +88,45 @@ async fn main() {
                         }
                     }
                 }
+                // Typed columns: `user_address` is stored as `address`, the query doesn't care
+                let bob = user_info_table
+                    .find_one_by(UserInfo::col.email, "bob@example.com".to_string())
+                    .await
+                    .unwrap()
+                    .expect("Expected to find Bob by email");
+                assert_eq!(bob.name, "Bob");
+                let addresses = user_info_table
+                    .select_column(UserInfo::col.user_address)
+                    .await
+                    .unwrap();
+                assert_eq!(addresses.len(), 2, "Expected 2 addresses");
+                let adults = user_info_table
+                    .find()
+                    .filter(
+                        UserInfo::col
+                            .age
+                            .ge(Some(18))
+                            .and(UserInfo::col.is_active.eq(true)),
+                    )
+                    .filter(
+                        UserInfo::col
+                            .name
+                            .like("B%")
+                            .or(UserInfo::col.age.is_null()),
+                    )
+                    .order_by(UserInfo::col.name)
+                    .limit(10)
+                    .fetch_all()
+                    .await
+                    .unwrap();
+                assert_eq!(adults.len(), 1, "Expected only Bob to match");
+                let active = user_info_table
+                    .find()
+                    .filter(UserInfo::col.is_active.eq(true))
+                    .count()
+                    .await
+                    .unwrap();
+                assert_eq!(active, 2, "Expected 2 active users");
             }
         } else {
             panic!("Expected id to be an array of u64 values");
@@ -0,0 +1,179 @@
+//! Typed errors raised by generated CRUD operations.
+//!
+//! The generated implementations report failures as `sqlx::Error`. Conditions detected by
+//! this crate rather than by the database driver are wrapped in `sqlx::Error::Database`,
+//! so they travel through the same error type and can be recovered with `from_error`.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use sqlx::error::{DatabaseError, ErrorKind};
+
+/// Returned by `update_by_id` on a table with a `#[crud(version)]` column when no row
+/// matched both the primary key and the expected version.
+///
+/// This means the row was changed (or deleted) by someone else since it was read.
+///
+/// # Example
+/// ```rust,ignore
+/// use typed_sqlx_client::VersionConflict;
+///
+/// match table.update_by_id(&id, &user).await {
+///     Ok(_) => {}
+///     Err(e) if VersionConflict::from_error(&e).is_some() => {
+///         // reload the row and retry
+///     }
+///     Err(e) => return Err(e),
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct VersionConflict {
+    table: &'static str,
+    message: String,
+}
+
+impl VersionConflict {
+    /// Create a conflict error for `table`.
+    pub fn new(table: &'static str) -> Self {
+        Self {
+            table,
+            message: format!(
+                "version conflict on table {}: the row was modified or deleted concurrently",
+                table
+            ),
+        }
+    }
+
+    /// The table whose update was rejected.
+    pub fn table_name(&self) -> &'static str {
+        self.table
+    }
+
+    /// Extract a `VersionConflict` from a `sqlx::Error`, if that is what it carries.
+    pub fn from_error(err: &sqlx::Error) -> Option<&Self> {
+        err.as_database_error()?.try_downcast_ref::<Self>()
+    }
+}
+
+impl fmt::Display for VersionConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for VersionConflict {}
+
+impl DatabaseError for VersionConflict {
+    fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn code(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn table(&self) -> Option<&str> {
+        Some(self.table)
+    }
+
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+/// Returned by `update_by_id`, `update_partial` and `delete_by_id` on a
+/// `#[crud(strict)]` table when no row has the given primary key.
+///
+/// # Example
+/// ```rust,ignore
+/// use typed_sqlx_client::NotFound;
+///
+/// if let Err(e) = table.delete_by_id(&id).await {
+///     if let Some(not_found) = NotFound::from_error(&e) {
+///         println!("no row {} in {}", not_found.id(), not_found.table_name());
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct NotFound {
+    table: &'static str,
+    id: String,
+    message: String,
+}
+
+impl NotFound {
+    /// Create a not-found error for the row of `table` keyed by `id`.
+    pub fn new(table: &'static str, id: &impl fmt::Debug) -> Self {
+        let id = format!("{:?}", id);
+        Self {
+            table,
+            message: format!("no row with id {} in table {}", id, table),
+            id,
+        }
+    }
+
+    /// The table that was searched.
+    pub fn table_name(&self) -> &'static str {
+        self.table
+    }
+
+    /// The missing primary key, formatted with `Debug`.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Extract a `NotFound` from a `sqlx::Error`, if that is what it carries.
+    pub fn from_error(err: &sqlx::Error) -> Option<&Self> {
+        err.as_database_error()?.try_downcast_ref::<Self>()
+    }
+}
+
+impl fmt::Display for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+impl DatabaseError for NotFound {
+    fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn code(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn table(&self) -> Option<&str> {
+        Some(self.table)
+    }
+
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
@@ -129,11 +129,63 @@
 //! | PostgreSQL | ✅ `db = "postgres"` | ✅ Both modes | Stable |
 //! | SQLite     | ✅ `db = "sqlite"` | ✅ Both modes | Stable |
 
+pub mod error;
+pub mod query;
+pub mod schema;
 pub mod tables;
 pub mod traits;
+pub mod transaction;
 
+pub use error::*;
+pub use query::*;
+pub use schema::*;
 pub use tables::*;
 pub use traits::*;
+pub use transaction::*;
 
-// Re-export the CrudOpsRef derive macro
-pub use typed_sqlx_client_macros::CrudOpsRef;
+// Re-export the derive macros
+pub use typed_sqlx_client_macros::{CrudOpsRef, TableDdl};
+
+// Gates for derives listing several backends, e.g. `#[crud(db = "postgres, sqlite")]`: each
+// backend's impls are kept only when the matching feature of this crate is enabled.
+#[doc(hidden)]
+#[cfg(feature = "mysql")]
+#[macro_export]
+macro_rules! __cfg_mysql {
+    ($($item:tt)*) => { $($item)* };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "mysql"))]
+#[macro_export]
+macro_rules! __cfg_mysql {
+    ($($item:tt)*) => {};
+}
+
+#[doc(hidden)]
+#[cfg(feature = "postgres")]
+#[macro_export]
+macro_rules! __cfg_postgres {
+    ($($item:tt)*) => { $($item)* };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "postgres"))]
+#[macro_export]
+macro_rules! __cfg_postgres {
+    ($($item:tt)*) => {};
+}
+
+#[doc(hidden)]
+#[cfg(feature = "sqlite")]
+#[macro_export]
+macro_rules! __cfg_sqlite {
+    ($($item:tt)*) => { $($item)* };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "sqlite"))]
+#[macro_export]
+macro_rules! __cfg_sqlite {
+    ($($item:tt)*) => {};
+}
@@ -0,0 +1,657 @@
+//! Typed column handles and the `SqlTable` queries built from them.
+//!
+//! `#[derive(CrudOpsRef)]` generates a [`TypedColumn`] for every stored field, reachable as
+//! `User::col.email`. It carries the SQL column name and the field's Rust type, so the query
+//! helpers below take columns instead of strings: renaming a field's column with
+//! `#[crud(rename = "...")]` only changes the generated name, and removing a field or changing
+//! its type turns every query that uses it into a compile error.
+//!
+//! [`SqlTable::find`] builds filtered, sorted and paged `SELECT`s from [`Condition`]s such as
+//! `User::col.age.ge(18)`, binding every value as a parameter.
+
+use crate::tables::SqlTable;
+use crate::traits::TableDialect;
+use sqlx::error::BoxDynError;
+use sqlx::{
+    Arguments, ColumnIndex, Database, Decode, Encode, Executor, FromRow, IntoArguments, Type,
+};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A column of the table of `Entity` whose values have the Rust type `T`.
+///
+/// Generated by `#[derive(CrudOpsRef)]` as the fields of `Entity::col`.
+///
+/// ## Example
+/// ```rust,ignore
+/// #[derive(FromRow, CrudOpsRef)]
+/// #[crud(table = "users", db = "postgres")]
+/// struct User {
+///     id: i64,
+///     #[crud(rename = "email_address")]
+///     email: String,
+/// }
+///
+/// assert_eq!(User::col.email.name(), "email_address");
+/// let users = db.get_table::<User>();
+/// let alice = users.find_one_by(User::col.email, "alice@example.com".to_string()).await?;
+/// ```
+pub struct TypedColumn<Entity, T> {
+    name: &'static str,
+    field: &'static str,
+    marker: PhantomData<fn() -> (Entity, T)>,
+}
+
+impl<Entity, T> TypedColumn<Entity, T> {
+    /// A column named `name` in SQL, mapped to the Rust field `field`.
+    pub const fn new(name: &'static str, field: &'static str) -> Self {
+        Self {
+            name,
+            field,
+            marker: PhantomData,
+        }
+    }
+
+    /// Name of the SQL column, unquoted.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Name of the Rust field.
+    pub fn field(&self) -> &'static str {
+        self.field
+    }
+}
+
+impl<Entity, T> Clone for TypedColumn<Entity, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Entity, T> Copy for TypedColumn<Entity, T> {}
+
+impl<Entity, T> fmt::Debug for TypedColumn<Entity, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedColumn")
+            .field("name", &self.name)
+            .field("field", &self.field)
+            .field("rust_type", &std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+impl<P: Database, DB, Entity: TableDialect<P>> SqlTable<P, DB, Entity> {
+    /// The table name as used in generated SQL: quoted for the backend and qualified with the
+    /// schema of [`SqlDB::with_schema`](crate::SqlDB::with_schema) or `#[crud(schema)]`.
+    pub fn table_sql(&self) -> String {
+        Entity::table_sql(self.schema())
+    }
+
+    /// `column` quoted for the backend, for hand-written SQL.
+    ///
+    /// ## Example
+    /// ```rust,ignore
+    /// let sql = format!(
+    ///     "SELECT COUNT(*) FROM {} WHERE {} > ?",
+    ///     users.table_sql(),
+    ///     users.column_sql(User::col.age),
+    /// );
+    /// ```
+    pub fn column_sql<T>(&self, column: TypedColumn<Entity, T>) -> String {
+        Entity::quote_ident(column.name())
+    }
+
+    /// `SELECT * FROM <table> WHERE <column> = <placeholder>`, plus the soft delete condition
+    /// if the struct has one.
+    fn select_where<T>(&self, column: TypedColumn<Entity, T>) -> String {
+        let mut sql = format!(
+            "SELECT * FROM {} WHERE {} = {}",
+            self.table_sql(),
+            self.column_sql(column),
+            Entity::placeholder(1)
+        );
+        if let Some(deleted) = Entity::soft_delete_column() {
+            sql.push_str(&format!(" AND {} IS NULL", Entity::quote_ident(deleted)));
+        }
+        sql
+    }
+}
+
+impl<P: Database, DB, Entity> SqlTable<P, DB, Entity>
+where
+    DB: Sync,
+    Entity: TableDialect<P> + for<'r> FromRow<'r, P::Row> + Send + Unpin,
+    for<'c> &'c mut P::Connection: Executor<'c, Database = P>,
+    for<'q> P::Arguments<'q>: IntoArguments<'q, P>,
+{
+    /// Every row where `column` equals `value`.
+    ///
+    /// Rows deleted through `#[crud(soft_delete)]` are skipped.
+    pub async fn find_by<T>(
+        &self,
+        column: TypedColumn<Entity, T>,
+        value: T,
+    ) -> Result<Vec<Entity>, sqlx::Error>
+    where
+        T: for<'q> Encode<'q, P> + Type<P> + Send,
+    {
+        let sql = self.select_where(column);
+        let mut conn = self.connection().await?;
+        sqlx::query_as(&sql).bind(value).fetch_all(&mut *conn).await
+    }
+
+    /// The first row where `column` equals `value`, if any.
+    ///
+    /// Rows deleted through `#[crud(soft_delete)]` are skipped.
+    pub async fn find_one_by<T>(
+        &self,
+        column: TypedColumn<Entity, T>,
+        value: T,
+    ) -> Result<Option<Entity>, sqlx::Error>
+    where
+        T: for<'q> Encode<'q, P> + Type<P> + Send,
+    {
+        let sql = format!("{} LIMIT 1", self.select_where(column));
+        let mut conn = self.connection().await?;
+        sqlx::query_as(&sql)
+            .bind(value)
+            .fetch_optional(&mut *conn)
+            .await
+    }
+
+    /// The values of `column` in every row, decoded as the field's type.
+    ///
+    /// Rows deleted through `#[crud(soft_delete)]` are skipped.
+    pub async fn select_column<T>(
+        &self,
+        column: TypedColumn<Entity, T>,
+    ) -> Result<Vec<T>, sqlx::Error>
+    where
+        T: for<'r> Decode<'r, P> + Type<P> + Send + Unpin,
+        usize: ColumnIndex<P::Row>,
+    {
+        let mut sql = format!(
+            "SELECT {} FROM {}",
+            self.column_sql(column),
+            self.table_sql()
+        );
+        if let Some(deleted) = Entity::soft_delete_column() {
+            sql.push_str(&format!(" WHERE {} IS NULL", Entity::quote_ident(deleted)));
+        }
+        let mut conn = self.connection().await?;
+        sqlx::query_scalar(&sql).fetch_all(&mut *conn).await
+    }
+}
+
+/// A value waiting to be bound, with its type erased so conditions on columns of different
+/// types can be combined.
+trait BindValue<P: Database>: Send {
+    fn add_to(self: Box<Self>, args: &mut P::Arguments<'_>) -> Result<(), BoxDynError>;
+}
+
+impl<P: Database, T> BindValue<P> for T
+where
+    T: for<'q> Encode<'q, P> + Type<P> + Send + 'static,
+{
+    fn add_to(self: Box<Self>, args: &mut P::Arguments<'_>) -> Result<(), BoxDynError> {
+        args.add(*self)
+    }
+}
+
+enum Node<P: Database> {
+    Compare {
+        column: &'static str,
+        op: &'static str,
+        value: Box<dyn BindValue<P>>,
+    },
+    In {
+        column: &'static str,
+        values: Vec<Box<dyn BindValue<P>>>,
+    },
+    IsNull {
+        column: &'static str,
+        negated: bool,
+    },
+    And(Vec<Node<P>>),
+    Or(Vec<Node<P>>),
+}
+
+impl<P: Database> Node<P> {
+    /// Append the SQL of the condition, numbering placeholders from `next`.
+    fn write_sql<Entity: TableDialect<P>>(&self, sql: &mut String, next: &mut usize) {
+        let mut placeholder_next = || {
+            let placeholder = Entity::placeholder(*next);
+            *next += 1;
+            placeholder
+        };
+        match self {
+            Node::Compare { column, op, .. } => {
+                let placeholder = placeholder_next();
+                sql.push_str(&format!(
+                    "{} {} {}",
+                    Entity::quote_ident(column),
+                    op,
+                    placeholder
+                ));
+            }
+            // `IN ()` is not valid SQL; an empty list matches nothing
+            Node::In { values, .. } if values.is_empty() => sql.push_str("1 = 0"),
+            Node::In { column, values } => {
+                let placeholders: Vec<String> = values.iter().map(|_| placeholder_next()).collect();
+                sql.push_str(&format!(
+                    "{} IN ({})",
+                    Entity::quote_ident(column),
+                    placeholders.join(", ")
+                ));
+            }
+            Node::IsNull { column, negated } => {
+                let check = if *negated { "IS NOT NULL" } else { "IS NULL" };
+                sql.push_str(&format!("{} {}", Entity::quote_ident(column), check));
+            }
+            Node::And(nodes) | Node::Or(nodes) => {
+                let joiner = if matches!(self, Node::And(_)) {
+                    " AND "
+                } else {
+                    " OR "
+                };
+                sql.push('(');
+                for (index, node) in nodes.iter().enumerate() {
+                    if index > 0 {
+                        sql.push_str(joiner);
+                    }
+                    node.write_sql::<Entity>(sql, next);
+                }
+                sql.push(')');
+            }
+        }
+    }
+
+    /// Bind the values in the order [`write_sql`](Self::write_sql) numbered them.
+    fn bind(self, args: &mut P::Arguments<'_>) -> Result<(), BoxDynError> {
+        match self {
+            Node::Compare { value, .. } => value.add_to(args),
+            Node::In { values, .. } => values.into_iter().try_for_each(|value| value.add_to(args)),
+            Node::IsNull { .. } => Ok(()),
+            Node::And(nodes) | Node::Or(nodes) => {
+                nodes.into_iter().try_for_each(|node| node.bind(args))
+            }
+        }
+    }
+}
+
+/// A `WHERE` condition on the table of `Entity`, built from its [`TypedColumn`]s and passed to
+/// [`FindQuery::filter`]. Values are bound as parameters.
+///
+/// ```rust,ignore
+/// let adults_named_a = User::col.age.ge(18).and(User::col.name.like("A%"));
+/// let staff = User::col.role.in_list([Role::Admin, Role::Support]).or(User::col.id.eq(1));
+/// ```
+pub struct Condition<P: Database, Entity> {
+    node: Node<P>,
+    marker: PhantomData<fn() -> Entity>,
+}
+
+impl<P: Database, Entity> Condition<P, Entity> {
+    fn new(node: Node<P>) -> Self {
+        Self {
+            node,
+            marker: PhantomData,
+        }
+    }
+
+    /// Both this condition and `other`.
+    pub fn and(self, other: Condition<P, Entity>) -> Self {
+        Self::new(match (self.node, other.node) {
+            (Node::And(mut nodes), Node::And(more)) => {
+                nodes.extend(more);
+                Node::And(nodes)
+            }
+            (Node::And(mut nodes), node) => {
+                nodes.push(node);
+                Node::And(nodes)
+            }
+            (node, other) => Node::And(vec![node, other]),
+        })
+    }
+
+    /// This condition or `other`.
+    pub fn or(self, other: Condition<P, Entity>) -> Self {
+        Self::new(match (self.node, other.node) {
+            (Node::Or(mut nodes), Node::Or(more)) => {
+                nodes.extend(more);
+                Node::Or(nodes)
+            }
+            (Node::Or(mut nodes), node) => {
+                nodes.push(node);
+                Node::Or(nodes)
+            }
+            (node, other) => Node::Or(vec![node, other]),
+        })
+    }
+}
+
+impl<Entity, T> TypedColumn<Entity, T> {
+    fn compare<P: Database>(self, op: &'static str, value: T) -> Condition<P, Entity>
+    where
+        T: for<'q> Encode<'q, P> + Type<P> + Send + 'static,
+    {
+        Condition::new(Node::Compare {
+            column: self.name,
+            op,
+            value: Box::new(value),
+        })
+    }
+
+    /// `column = value`. Use [`is_null`](Self::is_null) to match `NULL`.
+    pub fn eq<P: Database>(self, value: T) -> Condition<P, Entity>
+    where
+        T: for<'q> Encode<'q, P> + Type<P> + Send + 'static,
+    {
+        self.compare("=", value)
+    }
+
+    /// `column <> value`.
+    pub fn ne<P: Database>(self, value: T) -> Condition<P, Entity>
+    where
+        T: for<'q> Encode<'q, P> + Type<P> + Send + 'static,
+    {
+        self.compare("<>", value)
+    }
+
+    /// `column < value`.
+    pub fn lt<P: Database>(self, value: T) -> Condition<P, Entity>
+    where
+        T: for<'q> Encode<'q, P> + Type<P> + Send + 'static,
+    {
+        self.compare("<", value)
+    }
+
+    /// `column <= value`.
+    pub fn le<P: Database>(self, value: T) -> Condition<P, Entity>
+    where
+        T: for<'q> Encode<'q, P> + Type<P> + Send + 'static,
+    {
+        self.compare("<=", value)
+    }
+
+    /// `column > value`.
+    pub fn gt<P: Database>(self, value: T) -> Condition<P, Entity>
+    where
+        T: for<'q> Encode<'q, P> + Type<P> + Send + 'static,
+    {
+        self.compare(">", value)
+    }
+
+    /// `column >= value`.
+    pub fn ge<P: Database>(self, value: T) -> Condition<P, Entity>
+    where
+        T: for<'q> Encode<'q, P> + Type<P> + Send + 'static,
+    {
+        self.compare(">=", value)
+    }
+
+    /// `column IN (...)` over `values`. An empty list matches no rows.
+    pub fn in_list<P: Database>(self, values: impl IntoIterator<Item = T>) -> Condition<P, Entity>
+    where
+        T: for<'q> Encode<'q, P> + Type<P> + Send + 'static,
+    {
+        Condition::new(Node::In {
+            column: self.name,
+            values: values
+                .into_iter()
+                .map(|value| Box::new(value) as Box<dyn BindValue<P>>)
+                .collect(),
+        })
+    }
+
+    /// `column IS NULL`.
+    pub fn is_null<P: Database>(self) -> Condition<P, Entity> {
+        Condition::new(Node::IsNull {
+            column: self.name,
+            negated: false,
+        })
+    }
+
+    /// `column IS NOT NULL`.
+    pub fn is_not_null<P: Database>(self) -> Condition<P, Entity> {
+        Condition::new(Node::IsNull {
+            column: self.name,
+            negated: true,
+        })
+    }
+}
+
+impl<Entity> TypedColumn<Entity, String> {
+    /// `column LIKE pattern`, with `%` and `_` as wildcards. Case sensitivity follows the
+    /// backend: PostgreSQL is case-sensitive, MySQL and SQLite are not for ASCII text.
+    pub fn like<P: Database>(self, pattern: impl Into<String>) -> Condition<P, Entity>
+    where
+        String: for<'q> Encode<'q, P> + Type<P>,
+    {
+        like(self.name, pattern.into())
+    }
+}
+
+impl<Entity> TypedColumn<Entity, Option<String>> {
+    /// `column LIKE pattern`, with `%` and `_` as wildcards. `NULL` values never match.
+    pub fn like<P: Database>(self, pattern: impl Into<String>) -> Condition<P, Entity>
+    where
+        String: for<'q> Encode<'q, P> + Type<P>,
+    {
+        like(self.name, pattern.into())
+    }
+}
+
+/// `column LIKE pattern`, for the text columns.
+fn like<P: Database, Entity>(column: &'static str, pattern: String) -> Condition<P, Entity>
+where
+    String: for<'q> Encode<'q, P> + Type<P>,
+{
+    Condition::new(Node::Compare {
+        column,
+        op: "LIKE",
+        value: Box::new(pattern),
+    })
+}
+
+/// A `SELECT` on one table built from typed conditions, started with [`SqlTable::find`].
+///
+/// Filter values, `LIMIT` and `OFFSET` are bound as parameters with the backend's
+/// placeholders. Rows deleted through `#[crud(soft_delete)]` are skipped unless
+/// [`with_deleted`](Self::with_deleted) is called.
+///
+/// ## Example
+/// ```rust,ignore
+/// let page = users
+///     .find()
+///     .filter(User::col.age.ge(18).and(User::col.name.like("A%")))
+///     .filter(User::col.role.in_list([Role::Admin, Role::Support]).or(User::col.email.is_null()))
+///     .order_by_desc(User::col.created_at)
+///     .limit(20)
+///     .offset(40)
+///     .fetch_all()
+///     .await?;
+///
+/// let total = users.find().filter(User::col.age.ge(18)).count().await?;
+/// ```
+pub struct FindQuery<'a, P: Database, DB, Entity> {
+    table: &'a SqlTable<P, DB, Entity>,
+    filter: Option<Condition<P, Entity>>,
+    order_by: Vec<(&'static str, &'static str)>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+    with_deleted: bool,
+}
+
+impl<P: Database, DB, Entity: TableDialect<P>> SqlTable<P, DB, Entity> {
+    /// Start a [`FindQuery`] on this table, matching every row until filtered.
+    pub fn find(&self) -> FindQuery<'_, P, DB, Entity> {
+        FindQuery {
+            table: self,
+            filter: None,
+            order_by: Vec::new(),
+            limit: None,
+            offset: None,
+            with_deleted: false,
+        }
+    }
+}
+
+impl<'a, P: Database, DB, Entity: TableDialect<P>> FindQuery<'a, P, DB, Entity> {
+    /// Keep only rows matching `condition`. Several filters must all match.
+    pub fn filter(mut self, condition: Condition<P, Entity>) -> Self {
+        self.filter = Some(match self.filter.take() {
+            Some(filter) => filter.and(condition),
+            None => condition,
+        });
+        self
+    }
+
+    /// Sort by `column`, ascending. Further calls add tie-breakers.
+    pub fn order_by<T>(mut self, column: TypedColumn<Entity, T>) -> Self {
+        self.order_by.push((column.name(), "ASC"));
+        self
+    }
+
+    /// Sort by `column`, descending. Further calls add tie-breakers.
+    pub fn order_by_desc<T>(mut self, column: TypedColumn<Entity, T>) -> Self {
+        self.order_by.push((column.name(), "DESC"));
+        self
+    }
+
+    /// Return at most `limit` rows.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skip the first `offset` rows.
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Include rows deleted through `#[crud(soft_delete)]`.
+    pub fn with_deleted(mut self) -> Self {
+        self.with_deleted = true;
+        self
+    }
+
+    /// The `SELECT` statement [`fetch_all`](Self::fetch_all) runs, with placeholders for the
+    /// bound values.
+    pub fn to_sql(&self) -> String {
+        let mut next = 1;
+        self.select_sql("*", true, &mut next)
+    }
+
+    /// `SELECT <what> FROM <table> WHERE ...`, with `ORDER BY`, `LIMIT` and `OFFSET` if
+    /// `paged`.
+    fn select_sql(&self, what: &str, paged: bool, next: &mut usize) -> String {
+        let mut sql = format!("SELECT {} FROM {}", what, self.table.table_sql());
+        let deleted = Entity::soft_delete_column().filter(|_| !self.with_deleted);
+        if let Some(filter) = &self.filter {
+            sql.push_str(" WHERE ");
+            filter.node.write_sql::<Entity>(&mut sql, next);
+        }
+        if let Some(deleted) = deleted {
+            let joiner = if self.filter.is_some() {
+                " AND "
+            } else {
+                " WHERE "
+            };
+            sql.push_str(&format!(
+                "{}{} IS NULL",
+                joiner,
+                Entity::quote_ident(deleted)
+            ));
+        }
+        if !paged {
+            return sql;
+        }
+        if !self.order_by.is_empty() {
+            let order: Vec<String> = self
+                .order_by
+                .iter()
+                .map(|(column, direction)| format!("{} {}", Entity::quote_ident(column), direction))
+                .collect();
+            sql.push_str(&format!(" ORDER BY {}", order.join(", ")));
+        }
+        if self.limit.is_some() {
+            sql.push_str(&format!(" LIMIT {}", Entity::placeholder(*next)));
+            *next += 1;
+        } else if let (Some(_), Some(limit)) = (self.offset, Entity::unbounded_limit()) {
+            sql.push_str(&format!(" {}", limit));
+        }
+        if self.offset.is_some() {
+            sql.push_str(&format!(" OFFSET {}", Entity::placeholder(*next)));
+            *next += 1;
+        }
+        sql
+    }
+
+    /// Bind the filter values, then `LIMIT` and `OFFSET` if `paged`.
+    fn bind(self, args: &mut P::Arguments<'_>, paged: bool) -> Result<(), sqlx::Error>
+    where
+        i64: for<'q> Encode<'q, P> + Type<P>,
+    {
+        if let Some(filter) = self.filter {
+            filter.node.bind(args).map_err(sqlx::Error::Encode)?;
+        }
+        if paged {
+            for value in [self.limit, self.offset].into_iter().flatten() {
+                let value = i64::try_from(value).unwrap_or(i64::MAX);
+                args.add(value).map_err(sqlx::Error::Encode)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, P: Database, DB, Entity> FindQuery<'a, P, DB, Entity>
+where
+    DB: Sync,
+    Entity: TableDialect<P> + for<'r> FromRow<'r, P::Row> + Send + Unpin,
+    for<'c> &'c mut P::Connection: Executor<'c, Database = P>,
+    for<'q> P::Arguments<'q>: IntoArguments<'q, P>,
+    i64: for<'q> Encode<'q, P> + Type<P>,
+{
+    /// Every matching row.
+    pub async fn fetch_all(self) -> Result<Vec<Entity>, sqlx::Error> {
+        let table = self.table;
+        let sql = self.to_sql();
+        let mut args = P::Arguments::default();
+        self.bind(&mut args, true)?;
+        let mut conn = table.connection().await?;
+        sqlx::query_as_with(&sql, args).fetch_all(&mut *conn).await
+    }
+
+    /// The first matching row, if any. Adds `LIMIT 1` unless a limit is set.
+    pub async fn fetch_optional(mut self) -> Result<Option<Entity>, sqlx::Error> {
+        self.limit.get_or_insert(1);
+        let table = self.table;
+        let sql = self.to_sql();
+        let mut args = P::Arguments::default();
+        self.bind(&mut args, true)?;
+        let mut conn = table.connection().await?;
+        sqlx::query_as_with(&sql, args)
+            .fetch_optional(&mut *conn)
+            .await
+    }
+
+    /// The number of matching rows. Ordering, `limit` and `offset` do not apply.
+    pub async fn count(self) -> Result<i64, sqlx::Error>
+    where
+        i64: for<'r> Decode<'r, P>,
+        usize: ColumnIndex<P::Row>,
+    {
+        let table = self.table;
+        let sql = self.select_sql("COUNT(*)", false, &mut 1);
+        let mut args = P::Arguments::default();
+        self.bind(&mut args, false)?;
+        let mut conn = table.connection().await?;
+        sqlx::query_scalar_with(&sql, args)
+            .fetch_one(&mut *conn)
+            .await
+    }
+}
@@ -0,0 +1,558 @@
+//! Checks of derived structs against the live database schema.
+//!
+//! `SchemaVerify::verify_schema`, implemented by `#[derive(CrudOpsRef)]`, reads the table's
+//! columns from `information_schema` (MySQL, PostgreSQL) or `PRAGMA table_info` (SQLite) and
+//! reports every difference from the struct as a [`SchemaIssue`]. Running it at startup turns
+//! a missing column into a clear error before the first query hits it.
+//!
+//! A [`MigrationPlan`] goes one step further: it turns those differences into the
+//! `CREATE TABLE` and `ALTER TABLE` statements that reconcile the tables with their structs,
+//! and writes them as a migration file for `sqlx migrate`.
+
+use crate::tables::{SqlDB, SqlTable};
+use crate::traits::{SchemaVerify, TableDdl};
+use sqlx::Database;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One difference between a struct and the table it maps to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaIssue {
+    /// The table does not exist.
+    MissingTable,
+    /// A field's column does not exist in the table.
+    MissingColumn {
+        /// Name of the SQL column.
+        column: String,
+        /// Name of the Rust field.
+        field: String,
+    },
+    /// The table has a `NOT NULL` column without a default that the struct does not map, so
+    /// generated inserts cannot succeed.
+    UnmappedRequiredColumn {
+        /// Name of the SQL column.
+        column: String,
+    },
+    /// The column's type cannot be decoded into the field's Rust type.
+    TypeMismatch {
+        /// Name of the SQL column.
+        column: String,
+        /// The field's Rust type, e.g. `Option<String>`.
+        rust_type: String,
+        /// The column type reported by the database.
+        sql_type: String,
+    },
+}
+
+impl fmt::Display for SchemaIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaIssue::MissingTable => f.write_str("the table does not exist"),
+            SchemaIssue::MissingColumn { column, field } => {
+                write!(f, "column {} (field `{}`) does not exist", column, field)
+            }
+            SchemaIssue::UnmappedRequiredColumn { column } => write!(
+                f,
+                "column {} is NOT NULL without a default and has no field, so inserts fail",
+                column
+            ),
+            SchemaIssue::TypeMismatch {
+                column,
+                rust_type,
+                sql_type,
+            } => write!(
+                f,
+                "column {} has type {}, which does not decode as `{}`",
+                column, sql_type, rust_type
+            ),
+        }
+    }
+}
+
+/// Result of [`SchemaVerify::verify_schema`](crate::SchemaVerify::verify_schema): the
+/// differences found between a struct and its table.
+///
+/// ## Example
+/// ```rust,ignore
+/// use typed_sqlx_client::SchemaVerify;
+///
+/// // Refuse to start on a schema the structs do not match
+/// db.get_table::<User>().verify_schema().await?.into_result()?;
+/// db.get_table::<Order>().verify_schema().await?.into_result()?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaReport {
+    table: String,
+    issues: Vec<SchemaIssue>,
+    unmapped: Vec<String>,
+}
+
+impl SchemaReport {
+    /// An empty report for `table`.
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            issues: Vec::new(),
+            unmapped: Vec::new(),
+        }
+    }
+
+    /// The checked table, qualified with its schema if it has one.
+    pub fn table_name(&self) -> &str {
+        &self.table
+    }
+
+    /// Every difference found.
+    pub fn issues(&self) -> &[SchemaIssue] {
+        &self.issues
+    }
+
+    /// Columns of the table that no field maps to, including nullable ones and ones with a
+    /// default, which are not reported as issues.
+    pub fn unmapped_columns(&self) -> &[String] {
+        &self.unmapped
+    }
+
+    /// Returns `true` if the table matches the struct.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Record a difference.
+    pub fn push(&mut self, issue: SchemaIssue) {
+        self.issues.push(issue);
+    }
+
+    /// `Ok(())` if the table matches, or the report as an error.
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Compare the live columns of the table, as `(name, nullable, has_default)` rows, with the
+    /// `(column, field)` pairs of the struct. Returns the indices into `expected` of the
+    /// columns that exist.
+    #[doc(hidden)]
+    pub fn check_columns(
+        &mut self,
+        live: &[(String, i64, i64)],
+        expected: &[(&str, &str)],
+    ) -> Vec<usize> {
+        if live.is_empty() {
+            self.push(SchemaIssue::MissingTable);
+            return Vec::new();
+        }
+        let mut present = Vec::new();
+        for (index, (column, field)) in expected.iter().enumerate() {
+            if live.iter().any(|(name, _, _)| name == column) {
+                present.push(index);
+            } else {
+                self.push(SchemaIssue::MissingColumn {
+                    column: column.to_string(),
+                    field: field.to_string(),
+                });
+            }
+        }
+        for (name, nullable, has_default) in live {
+            if expected.iter().any(|(column, _)| column == name) {
+                continue;
+            }
+            if *nullable == 0 && *has_default == 0 {
+                self.push(SchemaIssue::UnmappedRequiredColumn {
+                    column: name.clone(),
+                });
+            }
+            self.unmapped.push(name.clone());
+        }
+        present
+    }
+}
+
+impl fmt::Display for SchemaReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "table {} matches its struct", self.table);
+        }
+        write!(f, "table {} does not match its struct:", self.table)?;
+        for issue in &self.issues {
+            write!(f, "\n  - {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SchemaReport {}
+
+/// One statement of a [`MigrationPlan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationStep {
+    /// A statement that only creates a table or adds a column.
+    Apply {
+        /// The table it changes, qualified with its schema if it has one.
+        table: String,
+        /// The statement, without a trailing `;`.
+        sql: String,
+    },
+    /// A column addition the database may reject, such as a `NOT NULL` column without a
+    /// default. It is written to the migration commented out, to be completed by hand.
+    Review {
+        /// The table it changes, qualified with its schema if it has one.
+        table: String,
+        /// The statement, without a trailing `;`.
+        sql: String,
+        /// Why the statement may fail.
+        reason: String,
+    },
+    /// A change that can lose data, such as dropping a column. It is written to the migration
+    /// commented out, to be reviewed and applied by hand.
+    Destructive {
+        /// The table it changes, qualified with its schema if it has one.
+        table: String,
+        /// The statement, or `None` if the backend has no statement for the change.
+        sql: Option<String>,
+        /// What the change does and why it needs review.
+        reason: String,
+    },
+}
+
+impl MigrationStep {
+    /// The table the step changes.
+    pub fn table_name(&self) -> &str {
+        match self {
+            MigrationStep::Apply { table, .. }
+            | MigrationStep::Review { table, .. }
+            | MigrationStep::Destructive { table, .. } => table,
+        }
+    }
+
+    /// Returns `true` for a step that can lose data.
+    pub fn is_destructive(&self) -> bool {
+        matches!(self, MigrationStep::Destructive { .. })
+    }
+
+    /// Returns `true` for a step that is flagged instead of applied.
+    pub fn needs_review(&self) -> bool {
+        !matches!(self, MigrationStep::Apply { .. })
+    }
+}
+
+/// The statements reconciling the tables of one `DB` marker with their structs, written as a
+/// migration file that `sqlx migrate` can apply.
+///
+/// Each table added with [`table`](Self::table) is checked with
+/// [`SchemaVerify::verify_schema`], and every difference becomes a step:
+///
+/// - a missing table is created with [`TableDdl::create_table_sql`],
+/// - a missing column is added with `ALTER TABLE ... ADD COLUMN`, unless it is `NOT NULL`, a
+///   key or (on SQLite) `UNIQUE`: those are flagged for review and written commented out, as
+///   the database may reject them (see [`TableDdl::add_column_caveat`]),
+/// - a column whose type does not decode as its field, and a column that no field maps to,
+///   are flagged as destructive: the type change or `DROP COLUMN` is written commented out.
+///
+/// The struct needs both `#[derive(CrudOpsRef)]` and `#[derive(TableDdl)]`.
+///
+/// ## Example
+/// ```rust,ignore
+/// let mut plan = db.migration_plan();
+/// plan.table::<User>().await?.table::<Order>().await?;
+///
+/// if plan.needs_review() {
+///     eprintln!("review the commented-out statements before applying");
+/// }
+/// // migrations/20261016093000_sync_tables.sql
+/// if let Some(path) = plan.write_migration("migrations", "sync tables")? {
+///     println!("wrote {}", path.display());
+/// }
+/// ```
+pub struct MigrationPlan<P: Database, DB> {
+    db: SqlDB<P, DB>,
+    steps: Vec<MigrationStep>,
+}
+
+impl<P: Database, DB> SqlDB<P, DB> {
+    /// Start an empty [`MigrationPlan`] for the tables of this database.
+    pub fn migration_plan(&self) -> MigrationPlan<P, DB> {
+        MigrationPlan {
+            db: self.clone(),
+            steps: Vec::new(),
+        }
+    }
+}
+
+impl<P: Database, DB> MigrationPlan<P, DB> {
+    /// Compare the table of `Table` with its struct and add the statements reconciling them.
+    pub async fn table<Table>(&mut self) -> Result<&mut Self, sqlx::Error>
+    where
+        SqlTable<P, DB, Table>:
+            SchemaVerify<Table, Error = sqlx::Error> + TableDdl<Table, Error = sqlx::Error>,
+    {
+        let table = self.db.get_table::<Table>();
+        let report = table.verify_schema().await?;
+        let name = report.table_name().to_string();
+        for issue in report.issues() {
+            let step = match issue {
+                SchemaIssue::MissingTable => MigrationStep::Apply {
+                    table: name.clone(),
+                    sql: table.create_table_sql(),
+                },
+                SchemaIssue::MissingColumn { column, .. } => {
+                    let Some(sql) = table.add_column_sql(column) else {
+                        continue;
+                    };
+                    match table.add_column_caveat(column) {
+                        Some(caveat) => MigrationStep::Review {
+                            table: name.clone(),
+                            sql,
+                            reason: format!("adds column {}, but {}", column, caveat),
+                        },
+                        None => MigrationStep::Apply {
+                            table: name.clone(),
+                            sql,
+                        },
+                    }
+                }
+                SchemaIssue::TypeMismatch {
+                    column,
+                    rust_type,
+                    sql_type,
+                } => MigrationStep::Destructive {
+                    table: name.clone(),
+                    sql: table.alter_column_type_sql(column),
+                    reason: format!(
+                        "changes the type of column {} from {} to fit `{}`",
+                        column, sql_type, rust_type
+                    ),
+                },
+                // Dropped below with the other unmapped columns
+                SchemaIssue::UnmappedRequiredColumn { .. } => continue,
+            };
+            self.steps.push(step);
+        }
+        for column in report.unmapped_columns() {
+            self.steps.push(MigrationStep::Destructive {
+                table: name.clone(),
+                sql: Some(table.drop_column_sql(column)),
+                reason: format!(
+                    "drops column {}, which no field maps to, and its data",
+                    column
+                ),
+            });
+        }
+        Ok(self)
+    }
+
+    /// Every step, in the order they are written.
+    pub fn steps(&self) -> &[MigrationStep] {
+        &self.steps
+    }
+
+    /// Returns `true` if every checked table matches its struct.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Returns `true` if any step is flagged as destructive.
+    pub fn has_destructive(&self) -> bool {
+        self.steps.iter().any(MigrationStep::is_destructive)
+    }
+
+    /// Returns `true` if any step is flagged, destructive or not, and left commented out.
+    pub fn needs_review(&self) -> bool {
+        self.steps.iter().any(MigrationStep::needs_review)
+    }
+
+    /// The migration script: the statements to apply, and the flagged ones commented out
+    /// below a `-- REVIEW` or `-- DESTRUCTIVE` note.
+    pub fn to_sql(&self) -> String {
+        let mut script =
+            String::from("-- Generated by typed_sqlx_client from the table structs.\n");
+        let mut current_table = None;
+        for step in &self.steps {
+            if current_table != Some(step.table_name()) {
+                current_table = Some(step.table_name());
+                script.push_str(&format!("\n-- {}\n", step.table_name()));
+            }
+            match step {
+                MigrationStep::Apply { sql, .. } => {
+                    script.push_str(sql);
+                    script.push_str(";\n");
+                }
+                MigrationStep::Review { sql, reason, .. } => {
+                    script.push_str(&format!("-- REVIEW, not applied: {}\n", reason));
+                    comment_out(&mut script, sql);
+                }
+                MigrationStep::Destructive { sql, reason, .. } => {
+                    script.push_str(&format!("-- DESTRUCTIVE, not applied: {}\n", reason));
+                    match sql {
+                        Some(sql) => comment_out(&mut script, sql),
+                        None => script.push_str(
+                            "-- (no statement for this backend; rebuild the table by hand)\n",
+                        ),
+                    }
+                }
+            }
+        }
+        script
+    }
+
+    /// Write [`to_sql`](Self::to_sql) to `dir` as `<timestamp>_<description>.sql`, the naming
+    /// `sqlx migrate add` uses, creating `dir` if needed. The timestamp is the current UTC time
+    /// as `YYYYMMDDHHMMSS`, and spaces in `description` become underscores.
+    ///
+    /// Returns the path of the new file, or `None` without writing anything if there are no
+    /// steps. A plan with only flagged steps still writes a file, all of it commented out.
+    pub fn write_migration(
+        &self,
+        dir: impl AsRef<Path>,
+        description: &str,
+    ) -> std::io::Result<Option<PathBuf>> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!(
+            "{}_{}.sql",
+            utc_timestamp(secs),
+            description.trim().replace(' ', "_")
+        ));
+        std::fs::write(&path, self.to_sql())?;
+        Ok(Some(path))
+    }
+}
+
+/// Append `sql` and its `;` to `script` as `--` comment lines.
+fn comment_out(script: &mut String, sql: &str) {
+    for line in format!("{};", sql).lines() {
+        script.push_str(&format!("-- {}\n", line));
+    }
+}
+
+/// `secs` since the Unix epoch as a UTC `YYYYMMDDHHMMSS` migration version.
+fn utc_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let time = secs % 86_400;
+    // Civil date from days since 1970-01-01 (proleptic Gregorian calendar)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utc_timestamp_formats_civil_dates() {
+        assert_eq!(utc_timestamp(0), "19700101000000");
+        assert_eq!(utc_timestamp(1_709_210_096), "20240229123456");
+        assert_eq!(utc_timestamp(1_709_251_199), "20240229235959");
+        assert_eq!(utc_timestamp(1_709_251_200), "20240301000000");
+        assert_eq!(utc_timestamp(1_704_067_199), "20231231235959");
+        assert_eq!(utc_timestamp(1_704_067_200), "20240101000000");
+    }
+
+    #[test]
+    fn check_columns_reports_missing_and_unmapped() {
+        let live = vec![
+            ("id".to_string(), 0, 0),
+            ("name".to_string(), 0, 0),
+            ("legacy".to_string(), 0, 0),
+            ("extra".to_string(), 1, 0),
+            ("created".to_string(), 0, 1),
+        ];
+        let mut report = SchemaReport::new("users");
+        let present = report.check_columns(
+            &live,
+            &[("id", "id"), ("email", "email"), ("name", "full_name")],
+        );
+        assert_eq!(present, vec![0, 2]);
+        assert_eq!(
+            report.issues(),
+            &[
+                SchemaIssue::MissingColumn {
+                    column: "email".to_string(),
+                    field: "email".to_string(),
+                },
+                SchemaIssue::UnmappedRequiredColumn {
+                    column: "legacy".to_string(),
+                },
+            ]
+        );
+        assert_eq!(report.unmapped_columns(), &["legacy", "extra", "created"]);
+
+        let mut report = SchemaReport::new("users");
+        assert!(report.check_columns(&[], &[("id", "id")]).is_empty());
+        assert_eq!(report.issues(), &[SchemaIssue::MissingTable]);
+        assert!(report.unmapped_columns().is_empty());
+    }
+
+    #[tokio::test]
+    async fn to_sql_comments_out_flagged_steps() {
+        let pool = sqlx::SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let mut plan = SqlDB::<sqlx::Sqlite, ()>::from_pool::<()>(pool).migration_plan();
+        assert!(plan.is_empty());
+        plan.steps = vec![
+            MigrationStep::Apply {
+                table: "users".to_string(),
+                sql: "CREATE TABLE users (\n  id INTEGER\n)".to_string(),
+            },
+            MigrationStep::Review {
+                table: "users".to_string(),
+                sql: "ALTER TABLE users ADD COLUMN name TEXT NOT NULL".to_string(),
+                reason: "adds column name".to_string(),
+            },
+            MigrationStep::Destructive {
+                table: "orders".to_string(),
+                sql: None,
+                reason: "changes the type of column total".to_string(),
+            },
+            MigrationStep::Destructive {
+                table: "orders".to_string(),
+                sql: Some("ALTER TABLE orders DROP COLUMN note".to_string()),
+                reason: "drops column note".to_string(),
+            },
+        ];
+        assert!(plan.needs_review());
+        assert!(plan.has_destructive());
+        assert_eq!(
+            plan.to_sql(),
+            "-- Generated by typed_sqlx_client from the table structs.\n\
+             \n\
+             -- users\n\
+             CREATE TABLE users (\n  id INTEGER\n);\n\
+             -- REVIEW, not applied: adds column name\n\
+             -- ALTER TABLE users ADD COLUMN name TEXT NOT NULL;\n\
+             \n\
+             -- orders\n\
+             -- DESTRUCTIVE, not applied: changes the type of column total\n\
+             -- (no statement for this backend; rebuild the table by hand)\n\
+             -- DESTRUCTIVE, not applied: drops column note\n\
+             -- ALTER TABLE orders DROP COLUMN note;\n"
+        );
+    }
+}
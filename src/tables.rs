@@ -1,9 +1,11 @@
 use crate::traits::SelectOnlyQuery;
+use crate::transaction::{Pinned, TableConnection};
 use sqlx::{
     database::Database, Column, ColumnIndex, Decode, Executor, IntoArguments, Pool, Row, Type,
 };
 use std::marker::PhantomData;
 use std::ops::Deref;
+use std::sync::Arc;
 
 /// Type-safe wrapper for a database connection pool.
 ///
@@ -63,7 +65,7 @@ use std::ops::Deref;
 /// ## Thread Safety
 /// `SqlDB` is `Send + Sync` and can be safely shared across async tasks and threads.
 /// The underlying sqlx pool uses `Arc` internally, making cloning very efficient.
-pub struct SqlDB<P: Database, DB>(Pool<P>, PhantomData<DB>);
+pub struct SqlDB<P: Database, DB>(Pool<P>, Option<Arc<str>>, PhantomData<DB>);
 
 /// Backward-compatible alias for `SqlDB`.
 pub type SqlPool<P, DB> = SqlDB<P, DB>;
@@ -98,6 +100,31 @@ impl<P: Database, DB> SqlDB<P, DB> {
     pub fn pool(&self) -> &Pool<P> {
         &self.0
     }
+
+    /// Point every table of this handle at `schema` instead of its `#[crud(schema = "...")]`.
+    ///
+    /// This lets the same entity types target another schema (or, on MySQL, another
+    /// database on the same server), e.g. a `staging` schema in tests. Tables declared
+    /// without a schema are qualified with it as well, and a dotted `table = "billing.invoices"`
+    /// keeps only its table part.
+    ///
+    /// ## Example
+    /// ```rust,ignore
+    /// struct StagingDB;
+    ///
+    /// let staging = SqlDB::from_pool::<StagingDB>(pg_pool).with_schema("staging");
+    /// let invoices = staging.get_table::<Invoice>();
+    /// assert_eq!(invoices.qualified_table_name(), "staging.invoices");
+    /// ```
+    pub fn with_schema(mut self, schema: impl Into<String>) -> Self {
+        self.1 = Some(Arc::from(schema.into()));
+        self
+    }
+
+    /// The schema set with [`with_schema`](Self::with_schema), if any.
+    pub fn schema(&self) -> Option<&str> {
+        self.1.as_deref()
+    }
 }
 
 impl<P: Database, DB> SqlDB<P, DB>
@@ -122,7 +149,7 @@ where
 /// so cloning a SqlDB is efficient and recommended for sharing across async tasks.
 impl<P: Database, DB> Clone for SqlDB<P, DB> {
     fn clone(&self) -> Self {
-        Self(self.0.clone(), PhantomData)
+        Self(self.0.clone(), self.1.clone(), PhantomData)
     }
 }
 
@@ -167,7 +194,7 @@ impl<P: Database> SqlDB<P, ()> {
     /// # Ok::<(), sqlx::Error>(())
     /// ```
     pub fn from_pool<DB>(pool: Pool<P>) -> SqlDB<P, DB> {
-        SqlDB(pool, PhantomData)
+        SqlDB(pool, None, PhantomData)
     }
 }
 
@@ -327,8 +354,13 @@ impl<P: Database> SqlDB<P, ()> {
 /// ## Thread Safety
 /// `SqlTable` is `Send + Sync` and can be safely shared across async tasks and threads.
 /// It's designed to be cloned efficiently for use in web handlers and async contexts.
+///
+/// ## Transactions
+/// Tables taken from [`SqlDB::get_table`] run each operation on a connection from the pool.
+/// Tables taken from a [`SqlTx`](crate::SqlTx) or [`SqlConn`](crate::SqlConn) run them on
+/// that transaction or connection instead.
 #[derive(Clone)]
-pub struct SqlTable<P: Database, DB, Table>(SqlDB<P, DB>, PhantomData<Table>);
+pub struct SqlTable<P: Database, DB, Table>(SqlDB<P, DB>, Option<Pinned<P>>, PhantomData<Table>);
 
 impl<P: Database, DB> SqlDB<P, DB> {
     /// Create a typed table handle for a specific entity.
@@ -390,7 +422,7 @@ impl<P: Database, DB> SqlDB<P, DB> {
     /// - The resulting `SqlTable` can be cloned efficiently
     /// - Multiple table handles can share the same underlying pool safely
     pub fn get_table<Table>(&self) -> SqlTable<P, DB, Table> {
-        SqlTable(self.clone(), PhantomData)
+        SqlTable(self.clone(), None, PhantomData)
     }
 }
 
@@ -404,6 +436,10 @@ impl<P: Database, DB, Table> SqlTable<P, DB, Table> {
     /// - Custom query builders or raw SQL
     /// - Pool monitoring and statistics
     ///
+    /// The pool is the same for a table taken from a [`SqlTx`](crate::SqlTx) or
+    /// [`SqlConn`](crate::SqlConn): queries run on it are outside that transaction or
+    /// connection. Use [`connection`](Self::connection) for work that should take part.
+    ///
     /// ## Example
     /// ```rust
     /// # use typed_sqlx_client::SqlTable;
@@ -429,6 +465,36 @@ impl<P: Database, DB, Table> SqlTable<P, DB, Table> {
     pub fn get_pool(&self) -> &Pool<P> {
         self.0.pool()
     }
+
+    /// The runtime schema override of the owning [`SqlDB`], if any.
+    pub fn schema(&self) -> Option<&str> {
+        self.0.schema()
+    }
+
+    /// The connection to run an operation on.
+    ///
+    /// For a table taken from a [`SqlTx`](crate::SqlTx) or [`SqlConn`](crate::SqlConn) this
+    /// locks the shared connection; otherwise a connection is acquired from the pool.
+    /// Generated operations use this rather than [`get_pool`](Self::get_pool) or the `Deref`
+    /// to the pool, both of which always run outside the transaction; custom table traits and
+    /// hand-written queries should use it too if they are meant to take part in transactions.
+    ///
+    /// The lock on a shared connection is held until the returned guard is dropped and is not
+    /// reentrant: running an operation on this or any other table of the same transaction or
+    /// connection while the guard is alive waits forever. Drop the guard first.
+    ///
+    /// ## Example
+    /// ```rust,ignore
+    /// let mut conn = user_table.connection().await?;
+    /// sqlx::query("UPDATE users SET active = false").execute(&mut *conn).await?;
+    /// ```
+    pub async fn connection(&self) -> Result<TableConnection<'_, P>, sqlx::Error> {
+        TableConnection::acquire(self.get_pool(), self.1.as_ref()).await
+    }
+
+    pub(crate) fn pinned(db: SqlDB<P, DB>, conn: Pinned<P>) -> Self {
+        SqlTable(db, Some(conn), PhantomData)
+    }
 }
 
 /// Allow passing SqlTable as `&Pool<P>` to sqlx queries
@@ -439,6 +505,8 @@ impl<P: Database, DB, Table> AsRef<Pool<P>> for SqlTable<P, DB, Table> {
 }
 
 /// Allow using `&SqlTable` as `&Pool<P>` directly
+///
+/// Queries run this way never join the table's transaction; see [`SqlTable::connection`].
 impl<P: Database, DB, Table> Deref for SqlTable<P, DB, Table> {
     type Target = Pool<P>;
 
@@ -453,7 +521,7 @@ where
     Table: Sync + Send,
     P::Row: Row<Database = P>,
     P::Column: Column<Database = P>,
-    for<'r> &'r Pool<P>: Executor<'r, Database = P>,
+    for<'c> &'c mut P::Connection: Executor<'c, Database = P>,
     for<'r> &'r str: ColumnIndex<P::Row>,
     for<'r> i64: Type<P> + Decode<'r, P>,
     for<'r> f64: Type<P> + Decode<'r, P>,
@@ -473,8 +541,8 @@ where
                 "Only SELECT queries are allowed".into(),
             ));
         }
-        let pool = self.get_pool();
-        let rows = sqlx::query(query).fetch_all(pool).await?;
+        let mut conn = self.connection().await?;
+        let rows = sqlx::query(query).fetch_all(&mut *conn).await?;
         let columns = if let Some(row) = rows.first() {
             row.columns()
                 .iter()
@@ -520,8 +588,8 @@ where
                 "Only SELECT queries are allowed".into(),
             ));
         }
-        let pool = self.get_pool();
-        let values: Vec<T> = sqlx::query_as(query).fetch_all(pool).await?;
+        let mut conn = self.connection().await?;
+        let values: Vec<T> = sqlx::query_as(query).fetch_all(&mut *conn).await?;
         Ok(values)
     }
 }
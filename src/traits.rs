@@ -13,7 +13,8 @@ use std::future::Future;
 /// - **Batch support**: Optimized batch insert operations
 ///
 /// ## Type Parameters
-/// * `ID` - The type of the primary key (e.g., `i64`, `Uuid`, `String`)
+/// * `ID` - The type of the primary key (e.g., `i64`, `Uuid`, `String`), or a tuple such as
+///   `(i64, String)` for composite keys
 /// * `Entity` - The entity/struct type representing a database row
 ///
 /// ## Implementation
@@ -76,7 +77,7 @@ use std::future::Future;
 /// # use typed_sqlx_client::CrudOpsRef;
 /// # async fn example(user_table: impl CrudOpsRef<uuid::Uuid, User, Error = sqlx::Error>, user: &User, id: &uuid::Uuid) {
 /// match user_table.insert(user).await {
-///     Ok(()) => println!("User inserted successfully"),
+///     Ok(_) => println!("User inserted successfully"),
 ///     Err(sqlx::Error::Database(db_err)) if db_err.constraint() == Some("users_email_key") => {
 ///         eprintln!("Email already exists");
 ///     }
@@ -89,21 +90,44 @@ pub trait CrudOpsRef<ID, Entity> {
     /// The error type for operations
     type Error;
 
+    /// Value returned by [`insert`](Self::insert).
+    ///
+    /// `()` when keys are assigned by the application, or the key type when the primary key
+    /// is marked `#[crud(primary_key, generated)]` and assigned by the database.
+    type InsertOutput;
+
+    /// Value returned by [`insert_batch`](Self::insert_batch).
+    ///
+    /// `()` when keys are assigned by the application, or a `Vec` of the generated keys in
+    /// input order when the primary key is marked `#[crud(primary_key, generated)]`.
+    type BatchInsertOutput;
+
     /// Returns the table name for this entity.
     ///
-    /// Such as `user_profiles`, `users`, etc.
+    /// Such as `user_profiles`, `users`, etc. This is the bare name, without any schema.
     fn table_name(&self) -> &'static str;
 
+    /// Returns the table name as used in generated SQL, qualified with its schema.
+    ///
+    /// Such as `billing.invoices` for `#[crud(table = "invoices", schema = "billing")]`.
+    /// A schema set with [`SqlDB::with_schema`](crate::SqlDB::with_schema) takes precedence.
+    /// Identifiers are returned unquoted. Defaults to [`table_name`](Self::table_name).
+    fn qualified_table_name(&self) -> String {
+        self.table_name().to_string()
+    }
+
     /// Insert a single entity into the database.
     ///
-    /// This method adds a new record to the database table. If the entity has an
-    /// auto-incrementing primary key, the database will assign the ID automatically.
+    /// This method adds a new record to the database table. If the primary key is marked
+    /// `#[crud(primary_key, generated)]`, the key column is left out of the statement and the
+    /// key assigned by the database is returned: via `RETURNING` on PostgreSQL and SQLite,
+    /// and via `last_insert_id()` on MySQL.
     ///
     /// ## Arguments
     /// * `entity` - A reference to the entity to insert
     ///
     /// ## Returns
-    /// * `Ok(())` if the insert was successful
+    /// * `Ok(Self::InsertOutput)` if the insert was successful: `()`, or the generated key
     /// * `Err(Self::Error)` if the insert failed (e.g., constraint violations, connection issues)
     ///
     /// ## Example
@@ -116,20 +140,57 @@ pub trait CrudOpsRef<ID, Entity> {
     /// # }
     /// # struct User { id: Option<i64>, name: String, email: String }
     /// ```
-    fn insert(&self, entity: &Entity) -> impl Future<Output = Result<(), Self::Error>> + Send;
+    fn insert(
+        &self,
+        entity: &Entity,
+    ) -> impl Future<Output = Result<Self::InsertOutput, Self::Error>> + Send;
+
+    /// Insert a single entity and return the row as stored by the database.
+    ///
+    /// Use this when column defaults, `BEFORE` triggers or computed columns change what ends
+    /// up in the row. PostgreSQL and SQLite read the row back with `RETURNING *`; MySQL runs the
+    /// insert and a follow-up `get_by_id` inside one transaction.
+    ///
+    /// ## Arguments
+    /// * `entity` - A reference to the entity to insert
+    ///
+    /// ## Returns
+    /// * `Ok(entity)` with the stored row
+    /// * `Err(Self::Error)` if the insert failed
+    ///
+    /// ## Example
+    /// ```rust
+    /// # use typed_sqlx_client::CrudOpsRef;
+    /// # async fn example(table: impl CrudOpsRef<i64, User, Error = sqlx::Error>) -> Result<(), sqlx::Error> {
+    /// let user = User { id: None, name: "Alice".to_string(), email: "alice@example.com".to_string() };
+    /// let stored = table.insert_returning(&user).await?;
+    /// println!("Inserted user with id {:?}", stored.id);
+    /// # Ok(())
+    /// # }
+    /// # struct User { id: Option<i64>, name: String, email: String }
+    /// ```
+    fn insert_returning(
+        &self,
+        entity: &Entity,
+    ) -> impl Future<Output = Result<Entity, Self::Error>> + Send;
 
     /// Insert multiple entities into the database in a batch operation.
     ///
-    /// This method performs batch insertion of multiple entities. The implementation
-    /// may optimize the operation by using transactions or bulk insert statements.
-    /// All inserts are performed atomically - if any insert fails, the entire
-    /// batch operation fails.
+    /// This method performs batch insertion of multiple entities. The derived implementation
+    /// sends multi-row `INSERT ... VALUES (...), (...)` statements, split into chunks so that
+    /// no statement exceeds the backend's bind parameter limit (65535 for PostgreSQL and MySQL,
+    /// `SQLITE_MAX_VARIABLE_NUMBER` for SQLite).
+    /// All chunks run inside a single transaction opened on the table's
+    /// [`connection`](crate::SqlTable::connection) - a savepoint when the table comes from a
+    /// [`SqlTx`](crate::SqlTx) - so the batch is all-or-nothing: if any insert fails, the
+    /// transaction is rolled back and no rows are written. Use [`insert_batch_best_effort`](Self::insert_batch_best_effort) to keep
+    /// the rows that succeed.
     ///
     /// ## Arguments
     /// * `entities` - A slice of entities to insert
     ///
     /// ## Returns
-    /// * `Ok(())` if all inserts were successful
+    /// * `Ok(Self::BatchInsertOutput)` if all inserts were successful: `()`, or the generated keys
     /// * `Err(Self::Error)` if any insert failed
     ///
     /// ## Example
@@ -148,7 +209,41 @@ pub trait CrudOpsRef<ID, Entity> {
     fn insert_batch(
         &self,
         entities: &[Entity],
-    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+    ) -> impl Future<Output = Result<Self::BatchInsertOutput, Self::Error>> + Send;
+
+    /// Insert multiple entities, keeping every row that succeeds.
+    ///
+    /// Unlike [`insert_batch`](Self::insert_batch), a failed row does not undo the others.
+    /// Rows are still sent as multi-row statements; when a statement fails, its rows are
+    /// retried one by one so that each failure can be attributed to a single entity. Each
+    /// attempt runs in its own nested transaction on the table's
+    /// [`connection`](crate::SqlTable::connection), so a failure inside a
+    /// [`SqlTx`](crate::SqlTx) only rolls back that attempt.
+    ///
+    /// ## Arguments
+    /// * `entities` - A slice of entities to insert
+    ///
+    /// ## Returns
+    /// * `Ok(report)` with the number of inserted rows and, for each failed row, its index
+    ///   in `entities` together with the error
+    /// * `Err(Self::Error)` if no connection could be acquired
+    ///
+    /// ## Example
+    /// ```rust
+    /// # use typed_sqlx_client::CrudOpsRef;
+    /// # async fn example(table: impl CrudOpsRef<i64, User, Error = sqlx::Error>, users: Vec<User>) -> Result<(), sqlx::Error> {
+    /// let report = table.insert_batch_best_effort(&users).await?;
+    /// for (index, error) in &report.failed {
+    ///     eprintln!("row {} was not inserted: {}", index, error);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # struct User { id: Option<i64>, name: String, email: String }
+    /// ```
+    fn insert_batch_best_effort(
+        &self,
+        entities: &[Entity],
+    ) -> impl Future<Output = Result<BatchInsertReport<Self::Error>, Self::Error>> + Send;
 
     /// Retrieve an entity by its primary key.
     ///
@@ -185,14 +280,17 @@ pub trait CrudOpsRef<ID, Entity> {
     ///
     /// This method performs an UPDATE query to modify an existing record in the database.
     /// All fields except the primary key are updated with values from the provided entity.
-    /// If no record with the given ID exists, the operation succeeds but affects 0 rows.
+    /// If no record with the given ID exists, the operation succeeds but affects 0 rows,
+    /// unless the struct is marked `#[crud(strict)]`, in which case it fails with
+    /// [`crate::NotFound`]. Tables with a `#[crud(version)]` column must match the entity's
+    /// version as well, and fail with [`crate::VersionConflict`] when no row matches.
     ///
     /// ## Arguments
     /// * `id` - A reference to the primary key value of the record to update
     /// * `entity` - A reference to the entity containing the new data
     ///
     /// ## Returns
-    /// * `Ok(())` if the update was successful (regardless of rows affected)
+    /// * `Ok(rows)` with the number of rows the update matched
     /// * `Err(Self::Error)` if the update failed
     ///
     /// ## Example
@@ -205,7 +303,9 @@ pub trait CrudOpsRef<ID, Entity> {
     ///     name: "Alice Updated".to_string(),
     ///     email: "alice.new@example.com".to_string()
     /// };
-    /// table.update_by_id(&user_id, &updated_user).await?;
+    /// if table.update_by_id(&user_id, &updated_user).await? == 0 {
+    ///     println!("User {} does not exist", user_id);
+    /// }
     /// # Ok(())
     /// # }
     /// # struct User { id: Option<i64>, name: String, email: String }
@@ -214,18 +314,53 @@ pub trait CrudOpsRef<ID, Entity> {
         &self,
         id: &ID,
         entity: &Entity,
-    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+    ) -> impl Future<Output = Result<u64, Self::Error>> + Send;
+
+    /// Update an existing entity by its primary key and return the row as stored.
+    ///
+    /// Behaves like [`update_by_id`](Self::update_by_id), then reads the updated row back:
+    /// with `RETURNING *` on PostgreSQL and SQLite, and with a follow-up `get_by_id` inside
+    /// the same transaction on MySQL.
+    ///
+    /// ## Arguments
+    /// * `id` - A reference to the primary key value of the record to update
+    /// * `entity` - A reference to the entity containing the new data
+    ///
+    /// ## Returns
+    /// * `Ok(Some(entity))` with the stored row
+    /// * `Ok(None)` if no entity with the given ID exists
+    /// * `Err(Self::Error)` if the update failed
+    ///
+    /// ## Example
+    /// ```rust
+    /// # use typed_sqlx_client::CrudOpsRef;
+    /// # async fn example(table: impl CrudOpsRef<i64, User, Error = sqlx::Error>, user: User) -> Result<(), sqlx::Error> {
+    /// if let Some(stored) = table.update_by_id_returning(&42, &user).await? {
+    ///     println!("Stored email: {}", stored.email);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # struct User { id: Option<i64>, name: String, email: String }
+    /// ```
+    fn update_by_id_returning(
+        &self,
+        id: &ID,
+        entity: &Entity,
+    ) -> impl Future<Output = Result<Option<Entity>, Self::Error>> + Send;
 
     /// Delete an entity by its primary key.
     ///
     /// This method performs a DELETE query to remove a record from the database.
-    /// The operation succeeds even if no record with the given ID exists.
+    /// For structs with `#[crud(soft_delete = "...")]` it sets that column to the current
+    /// timestamp instead (see [`SoftDeleteOps`]).
+    /// The operation succeeds even if no record with the given ID exists, unless the struct
+    /// is marked `#[crud(strict)]`, in which case it fails with [`crate::NotFound`].
     ///
     /// ## Arguments
     /// * `id` - A reference to the primary key value of the record to delete
     ///
     /// ## Returns
-    /// * `Ok(())` if the deletion was successful (even if no rows were affected)
+    /// * `Ok(rows)` with the number of rows deleted (0 if the record did not exist)
     /// * `Err(Self::Error)` if the deletion failed
     ///
     /// ## Example
@@ -233,13 +368,404 @@ pub trait CrudOpsRef<ID, Entity> {
     /// # use typed_sqlx_client::CrudOpsRef;
     /// # async fn example(table: impl CrudOpsRef<i64, User, Error = sqlx::Error>) -> Result<(), sqlx::Error> {
     /// let user_id = 42;
-    /// table.delete_by_id(&user_id).await?;
-    /// println!("User with ID {} has been deleted (or didn't exist)", user_id);
+    /// let deleted = table.delete_by_id(&user_id).await?;
+    /// println!("Deleted {} row(s) with ID {}", deleted, user_id);
     /// # Ok(())
     /// # }
     /// # struct User { id: Option<i64>, name: String, email: String }
     /// ```
-    fn delete_by_id(&self, id: &ID) -> impl Future<Output = Result<(), Self::Error>> + Send;
+    fn delete_by_id(&self, id: &ID) -> impl Future<Output = Result<u64, Self::Error>> + Send;
+}
+
+/// Trait for "insert or update" operations on database entities.
+///
+/// Implemented by `#[derive(CrudOpsRef)]` alongside [`CrudOpsRef`]. A conflict is detected on
+/// the struct-level `#[crud(conflict = "col1,col2")]` columns if given, otherwise on the field
+/// marked `#[crud(unique)]`, otherwise on the primary key. Each unique field is a separate
+/// `UNIQUE` constraint, so a struct with several of them must name its target with `conflict`;
+/// `#[derive(TableDdl)]` creates a composite `conflict` target as one `UNIQUE` constraint.
+///
+/// ## Generated SQL
+/// - **PostgreSQL / SQLite**: `INSERT ... ON CONFLICT (target) DO UPDATE SET col = EXCLUDED.col`
+/// - **MySQL**: `INSERT ... ON DUPLICATE KEY UPDATE col = VALUES(col)`. MySQL checks every
+///   unique index, so the conflict target only decides which columns are left untouched.
+///   `insert_ignore` uses `INSERT IGNORE`, which also turns other row errors such as
+///   truncation into warnings.
+///
+/// Every inserted column outside the conflict target is overwritten on conflict.
+///
+/// ## Example
+/// ```rust
+/// # use typed_sqlx_client::UpsertOps;
+/// # async fn example(table: impl UpsertOps<User, Error = sqlx::Error>, user: User) -> Result<(), sqlx::Error> {
+/// // Insert the user, or refresh the name if the email is already known
+/// table.upsert(&user).await?;
+///
+/// // Insert only if no row with the same key exists
+/// if !table.insert_ignore(&user).await? {
+///     println!("User already exists");
+/// }
+/// # Ok(())
+/// # }
+/// # struct User { id: Option<i64>, name: String, email: String }
+/// ```
+pub trait UpsertOps<Entity> {
+    /// The error type for operations
+    type Error;
+
+    /// Insert an entity, or update the existing row when the conflict target matches.
+    fn upsert(&self, entity: &Entity) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Upsert multiple entities using multi-row statements inside one transaction.
+    ///
+    /// PostgreSQL rejects a statement that touches the same row twice, so the same conflict
+    /// key must not appear more than once in `entities`.
+    fn upsert_batch(
+        &self,
+        entities: &[Entity],
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Insert an entity unless a row with the same conflict target already exists.
+    ///
+    /// Returns `true` if the row was inserted and `false` if it already existed.
+    fn insert_ignore(
+        &self,
+        entity: &Entity,
+    ) -> impl Future<Output = Result<bool, Self::Error>> + Send;
+}
+
+/// Trait for partial updates that only write the columns a caller actually changed.
+///
+/// `#[derive(CrudOpsRef)]` generates a `<Entity>Patch` struct (e.g. `UserPatch` for `User`)
+/// with every non-key field wrapped in `Option`, and implements this trait with it as
+/// [`Patch`](Self::Patch). Only fields set to `Some` appear in the `SET` clause, so two
+/// services editing different fields of the same row do not overwrite each other.
+/// For a nullable column, `Some(None)` sets it to `NULL`.
+///
+/// ## Example
+/// ```rust
+/// # use typed_sqlx_client::PartialUpdateOps;
+/// # async fn example(table: impl PartialUpdateOps<i64, User, Error = sqlx::Error, Patch = UserPatch>) -> Result<(), sqlx::Error> {
+/// let patch = UserPatch {
+///     email: Some("alice.new@example.com".to_string()),
+///     ..Default::default()
+/// };
+/// table.update_partial(&42, &patch).await?;
+/// # Ok(())
+/// # }
+/// # struct User;
+/// # #[derive(Default)] struct UserPatch { name: Option<String>, email: Option<String> }
+/// ```
+pub trait PartialUpdateOps<ID, Entity> {
+    /// The error type for operations
+    type Error;
+
+    /// The generated patch type holding an `Option` per non-key column.
+    type Patch;
+
+    /// Update the columns set in `patch` on the row with the given primary key.
+    ///
+    /// ## Returns
+    /// * `Ok(rows)` with the number of rows the update matched
+    /// * `Err(sqlx::Error::InvalidArgument)` from the derived implementation if the patch
+    ///   does not set any column
+    /// * `Err(crate::NotFound)` (wrapped in `sqlx::Error`) on a `#[crud(strict)]` table
+    ///   when no row has the key
+    /// * `Err(Self::Error)` if the update failed
+    fn update_partial(
+        &self,
+        id: &ID,
+        patch: &Self::Patch,
+    ) -> impl Future<Output = Result<u64, Self::Error>> + Send;
+}
+
+/// Trait for tables that mark rows as deleted instead of removing them.
+///
+/// Implemented by `#[derive(CrudOpsRef)]` when the struct carries
+/// `#[crud(soft_delete = "deleted_at")]`. With that option, the generated
+/// [`CrudOpsRef::delete_by_id`] sets the column to `CURRENT_TIMESTAMP` and
+/// [`CrudOpsRef::get_by_id`] ignores rows where it is not `NULL`. This trait provides
+/// access to the deleted rows.
+///
+/// ## Example
+/// ```rust
+/// # use typed_sqlx_client::{CrudOpsRef, SoftDeleteOps};
+/// # async fn example<T>(table: T) -> Result<(), sqlx::Error>
+/// # where T: CrudOpsRef<i64, User, Error = sqlx::Error> + SoftDeleteOps<i64, User, Error = sqlx::Error> {
+/// table.delete_by_id(&42).await?;                       // sets deleted_at
+/// assert!(table.get_by_id(&42).await?.is_none());       // hidden from normal reads
+/// assert!(table.get_by_id_with_deleted(&42).await?.is_some());
+/// table.restore_by_id(&42).await?;                      // clears deleted_at
+/// table.hard_delete_by_id(&42).await?;                  // physically removes the row
+/// # Ok(())
+/// # }
+/// # struct User;
+/// ```
+pub trait SoftDeleteOps<ID, Entity> {
+    /// The error type for operations
+    type Error;
+
+    /// The sqlx database driver type, used to decode rows in
+    /// [`execute_select_as_only_not_deleted`](Self::execute_select_as_only_not_deleted).
+    type Database: sqlx::Database;
+
+    /// Clear the soft-delete column of the row with the given primary key.
+    ///
+    /// Returns the number of rows affected, like [`CrudOpsRef::delete_by_id`]; with
+    /// `#[crud(strict)]` a missing row fails with [`crate::NotFound`].
+    fn restore_by_id(&self, id: &ID) -> impl Future<Output = Result<u64, Self::Error>> + Send;
+
+    /// Physically delete the row with the given primary key, deleted or not.
+    ///
+    /// Returns the number of rows deleted; with `#[crud(strict)]` a missing row fails with
+    /// [`crate::NotFound`].
+    fn hard_delete_by_id(&self, id: &ID) -> impl Future<Output = Result<u64, Self::Error>> + Send;
+
+    /// Retrieve an entity by its primary key, including soft-deleted rows.
+    fn get_by_id_with_deleted(
+        &self,
+        id: &ID,
+    ) -> impl Future<Output = Result<Option<Entity>, Self::Error>> + Send;
+
+    /// Like [`SelectOnlyQuery::execute_select_as_only`], but drops soft-deleted rows.
+    ///
+    /// The query is wrapped as `SELECT * FROM (<query>) AS soft_delete_scope WHERE
+    /// soft_delete_scope.<column> IS NULL`, so it must select the soft-delete column once, and
+    /// on MySQL no column name twice. The query is described first and rejected with
+    /// `sqlx::Error::InvalidArgument` otherwise.
+    fn execute_select_as_only_not_deleted<T>(
+        &self,
+        query: &str,
+    ) -> impl Future<Output = Result<Vec<T>, Self::Error>> + Send
+    where
+        T: for<'r> sqlx::FromRow<'r, <Self::Database as sqlx::Database>::Row>
+            + Send
+            + Unpin
+            + 'static;
+}
+
+/// Trait exposing the table layout described by a `#[derive(CrudOpsRef)]` struct.
+///
+/// Implemented by the derive on the struct itself, once for all backends, so tooling such as
+/// admin screens, validators or query builders can inspect a table without a database
+/// connection and without repeating the column list by hand.
+///
+/// ## Example
+/// ```rust,ignore
+/// use typed_sqlx_client::TableSchema;
+///
+/// #[derive(FromRow, CrudOpsRef)]
+/// #[crud(table = "users", db = "postgres")]
+/// struct User {
+///     #[crud(primary_key)]
+///     id: i64,
+///     #[crud(rename = "user_name")]
+///     name: String,
+///     email: Option<String>,
+/// }
+///
+/// assert_eq!(User::table_name(), "users");
+/// let name = User::column("name").unwrap();
+/// assert_eq!(name.column, "user_name");
+/// assert!(name.is_renamed());
+/// assert!(User::column("email").unwrap().nullable);
+/// assert_eq!(User::primary_key()[0].field, "id");
+/// ```
+pub trait TableSchema {
+    /// Table name from `#[crud(table = "...")]`, or the struct name, without schema.
+    fn table_name() -> &'static str;
+
+    /// Schema from `#[crud(schema = "...")]`, if set.
+    ///
+    /// A schema chosen at runtime with `SqlDB::with_schema` is not reflected here.
+    fn schema() -> Option<&'static str>;
+
+    /// Every stored field of the struct, in declaration order; `#[crud(skip)]` fields are
+    /// left out.
+    fn columns() -> &'static [ColumnInfo];
+
+    /// Column from `#[crud(soft_delete = "...")]`, if set: rows where it is not `NULL` are
+    /// deleted and skipped by reads.
+    fn soft_delete_column() -> Option<&'static str> {
+        None
+    }
+
+    /// The column for the Rust field named `field`.
+    fn column(field: &str) -> Option<&'static ColumnInfo> {
+        Self::columns().iter().find(|column| column.field == field)
+    }
+
+    /// The primary key columns, in declaration order.
+    ///
+    /// Without any `#[crud(primary_key)]` field this is the first field, as in the
+    /// generated CRUD statements.
+    fn primary_key() -> Vec<&'static ColumnInfo> {
+        Self::columns()
+            .iter()
+            .filter(|column| column.primary_key)
+            .collect()
+    }
+}
+
+/// How backend `P` writes the table, identifiers and parameters of a `#[derive(CrudOpsRef)]`
+/// struct.
+///
+/// Implemented by the derive on the struct for each backend it targets, with the same rules
+/// as its generated statements, so the [`TypedColumn`](crate::TypedColumn) queries of
+/// [`SqlTable`](crate::SqlTable) quote and number exactly like them.
+pub trait TableDialect<P: sqlx::Database>: TableSchema {
+    /// The quoted table name, qualified with `schema` (a `SqlDB::with_schema` override) if
+    /// given, otherwise with the `#[crud(schema = "...")]` one.
+    fn table_sql(schema: Option<&str>) -> String;
+
+    /// `name` quoted as an identifier; a name already in the backend's quotes is kept as is.
+    fn quote_ident(name: &str) -> String;
+
+    /// Bind placeholder for the 1-based parameter `index`: `$1` on PostgreSQL, `?` elsewhere.
+    fn placeholder(index: usize) -> String;
+
+    /// The `LIMIT` clause an `OFFSET` needs in front of it when no limit is set, on backends
+    /// that require one.
+    fn unbounded_limit() -> Option<&'static str>;
+}
+
+/// One column of a [`TableSchema`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnInfo {
+    /// Name of the Rust field.
+    pub field: &'static str,
+    /// Name of the SQL column, from `#[crud(rename = "...")]` or the field name.
+    pub column: &'static str,
+    /// The field's Rust type as written in the struct, e.g. `Option<String>`.
+    pub rust_type: &'static str,
+    /// Whether the field is an `Option<T>` and the column may hold `NULL`.
+    pub nullable: bool,
+    /// Whether the column is part of the primary key.
+    pub primary_key: bool,
+}
+
+impl ColumnInfo {
+    /// Returns `true` if the SQL column name differs from the field name.
+    pub fn is_renamed(&self) -> bool {
+        self.field != self.column
+    }
+}
+
+/// Trait for checking a table in the live database against its struct.
+///
+/// Implemented by `#[derive(CrudOpsRef)]`. The check reads the columns from
+/// `information_schema` (MySQL, PostgreSQL) or `PRAGMA table_info` (SQLite) and reports
+/// fields whose column is missing, `NOT NULL` columns without a default that no field maps,
+/// and columns whose type does not decode as the field's type (`sqlx::Type::compatible`).
+/// `#[crud(skip)]` fields are not checked.
+///
+/// ## Example
+/// ```rust,ignore
+/// use typed_sqlx_client::SchemaVerify;
+///
+/// let report = db.get_table::<User>().verify_schema().await?;
+/// if !report.is_ok() {
+///     eprintln!("{}", report);
+/// }
+/// ```
+pub trait SchemaVerify<Entity> {
+    /// The error type for operations
+    type Error;
+
+    /// Compare the table with the struct.
+    ///
+    /// ## Returns
+    /// * `Ok(report)` listing every difference; [`SchemaReport::is_ok`](crate::SchemaReport::is_ok)
+    ///   if there are none
+    /// * `Err(Self::Error)` if the schema could not be read
+    fn verify_schema(
+        &self,
+    ) -> impl Future<Output = Result<crate::SchemaReport, Self::Error>> + Send;
+}
+
+/// Trait for creating and dropping the table behind a [`SqlTable`](crate::SqlTable).
+///
+/// Implemented by `#[derive(TableDdl)]`, which builds the `CREATE TABLE` statement from the
+/// struct's fields and `#[crud(...)]` attributes: column types from the Rust types (or
+/// `#[crud(sql_type = "...")]`), `NOT NULL` for fields that are not `Option<T>` and
+/// `PRIMARY KEY` from `#[crud(primary_key)]`. The statements use the table's schema,
+/// including one set with `SqlDB::with_schema`.
+///
+/// ## Example
+/// ```rust,ignore
+/// use typed_sqlx_client::TableDdl;
+///
+/// let users = db.get_table::<User>();
+/// users.drop_table().await?;
+/// users.create_table_if_not_exists().await?;
+/// println!("{}", users.create_table_sql());
+/// ```
+pub trait TableDdl<Entity> {
+    /// The error type for operations
+    type Error;
+
+    /// The `CREATE TABLE` statement for this table.
+    fn create_table_sql(&self) -> String;
+
+    /// Create the table with `CREATE TABLE IF NOT EXISTS`.
+    ///
+    /// An existing table is left as it is, even if its columns differ.
+    fn create_table_if_not_exists(&self) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Drop the table with `DROP TABLE IF EXISTS`, deleting all of its rows.
+    fn drop_table(&self) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// The `ALTER TABLE ... ADD COLUMN` statement for the SQL column `column`, or `None` if
+    /// no field maps to it.
+    fn add_column_sql(&self, column: &str) -> Option<String>;
+
+    /// Why the [`add_column_sql`](Self::add_column_sql) statement of `column` may fail, or
+    /// `None` if it applies to any table.
+    ///
+    /// Set for `NOT NULL` columns, which have no default for existing rows (and which SQLite
+    /// never adds), for key columns, and on SQLite for `UNIQUE` columns.
+    fn add_column_caveat(&self, column: &str) -> Option<&'static str>;
+
+    /// The statement changing the type of the SQL column `column` to the one of its field.
+    ///
+    /// `None` if no field maps to the column, and always on SQLite, which cannot change a
+    /// column's type without rebuilding the table.
+    fn alter_column_type_sql(&self, column: &str) -> Option<String>;
+
+    /// The `ALTER TABLE ... DROP COLUMN` statement for the SQL column `column`.
+    fn drop_column_sql(&self, column: &str) -> String;
+}
+
+/// Outcome of [`CrudOpsRef::insert_batch_best_effort`].
+///
+/// Rows that are not listed in `failed` were inserted.
+#[derive(Debug)]
+pub struct BatchInsertReport<E> {
+    /// Number of rows that were inserted.
+    pub inserted: usize,
+    /// Index into the input slice and error for every row that could not be inserted.
+    pub failed: Vec<(usize, E)>,
+}
+
+impl<E> BatchInsertReport<E> {
+    /// Returns `true` if every row was inserted.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Indices of the rows that could not be inserted, in input order.
+    pub fn failed_indices(&self) -> Vec<usize> {
+        self.failed.iter().map(|(index, _)| *index).collect()
+    }
+}
+
+impl<E> Default for BatchInsertReport<E> {
+    fn default() -> Self {
+        Self {
+            inserted: 0,
+            failed: Vec::new(),
+        }
+    }
 }
 
 // /// Trait for async CRUD operations using owned entities.
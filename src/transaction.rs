@@ -0,0 +1,422 @@
+//! Transactions and pinned connections shared by several tables.
+//!
+//! A [`SqlTx`] (from [`SqlDB::begin`]) or [`SqlConn`] (from [`SqlDB::acquire`]) hands out
+//! [`SqlTable`] handles whose generated operations all run on the same underlying connection,
+//! so writes to different tables can be committed or rolled back together. Transactions nest:
+//! inner scopes are savepoints that can be rolled back on their own.
+
+use crate::tables::{SqlDB, SqlTable};
+use futures_util::lock::{Mutex, MutexGuard};
+use sqlx::database::Database;
+use sqlx::pool::PoolConnection;
+use sqlx::{Executor, Pool, Transaction};
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// The connection a [`SqlTx`] or [`SqlConn`] holds, as shared with a table taken from it.
+pub(crate) struct Pinned<P: Database> {
+    shared: Arc<Shared<P>>,
+    /// The `finished` flag of the transaction scope the table was taken from. A nested scope
+    /// ends while the connection stays open for the enclosing one, so the flag is checked too.
+    scope: Option<Arc<AtomicBool>>,
+}
+
+impl<P: Database> Clone for Pinned<P> {
+    fn clone(&self) -> Self {
+        Pinned {
+            shared: self.shared.clone(),
+            scope: self.scope.clone(),
+        }
+    }
+}
+
+pub(crate) struct Shared<P: Database> {
+    /// `None` once the transaction has been committed or rolled back.
+    conn: Mutex<Option<PinnedConnection<P>>>,
+    /// Outermost savepoint level dropped without commit or rollback, or 0.
+    abandoned: AtomicUsize,
+}
+
+pub(crate) enum PinnedConnection<P: Database> {
+    Transaction(Transaction<'static, P>),
+    Connection(PoolConnection<P>),
+}
+
+impl<P: Database> Shared<P> {
+    fn new(conn: PinnedConnection<P>) -> Arc<Self> {
+        Arc::new(Shared {
+            conn: Mutex::new(Some(conn)),
+            abandoned: AtomicUsize::new(0),
+        })
+    }
+}
+
+/// A database transaction, or a savepoint scope inside one, that keeps the `DB` marker of
+/// the [`SqlDB`] it was started from.
+///
+/// Tables taken with [`get_table`](Self::get_table) run every generated operation
+/// (`CrudOpsRef`, `UpsertOps`, `PartialUpdateOps`, `SoftDeleteOps`, `SelectOnlyQuery`) inside
+/// the transaction. Nothing is written until [`commit`](Self::commit); dropping the
+/// transaction, or calling [`rollback`](Self::rollback), discards the changes.
+///
+/// Operations on tables of one transaction are serialized, since they share one connection;
+/// see [`SqlTable::connection`] for holding it across several queries.
+///
+/// ## Example
+/// ```rust,ignore
+/// let tx = db.begin().await?;
+/// let orders = tx.get_table::<Order>();
+/// let stock = tx.get_table::<Stock>();
+///
+/// orders.insert(&order).await?;
+/// stock.update_by_id(&item.id, &item).await?;
+/// tx.commit().await?;
+/// ```
+///
+/// ## Nested Scopes
+/// [`begin`](Self::begin) and [`transaction`](Self::transaction) open an inner scope with
+/// `SAVEPOINT`, which works the same on MySQL, PostgreSQL and SQLite. Rolling the inner scope
+/// back only undoes what was written since it started; committing it releases the savepoint,
+/// and its changes become permanent with the outer transaction.
+///
+/// ```rust,ignore
+/// db.transaction(|tx| async move {
+///     tx.get_table::<Order>().insert(&order).await?;
+///     // A failed reservation is rolled back on its own; the order is kept.
+///     let reserved = tx
+///         .transaction(|tx| async move { reserve_stock(tx.get_table::<Stock>(), &order).await })
+///         .await;
+///     Ok::<_, sqlx::Error>(reserved.is_ok())
+/// })
+/// .await?;
+/// ```
+///
+/// Scopes of one transaction must not be open concurrently. An inner scope dropped without
+/// commit or rollback cannot be undone right away; committing any enclosing scope then
+/// fails with `sqlx::Error::InvalidArgument` and rolls that scope back instead.
+pub struct SqlTx<P: Database, DB> {
+    db: SqlDB<P, DB>,
+    shared: Arc<Shared<P>>,
+    /// 1 for the transaction itself, 2 and up for savepoints.
+    level: usize,
+    finished: Arc<AtomicBool>,
+    /// Set on the handle a `transaction` closure receives; the helper finishes the scope.
+    managed: bool,
+}
+
+impl<P: Database, DB> SqlTx<P, DB> {
+    /// Create a table handle whose operations run inside this transaction.
+    ///
+    /// Once the transaction is committed, rolled back or dropped, operations on the handle
+    /// fail with `sqlx::Error::InvalidArgument`. For a nested scope this happens when the
+    /// scope ends, although the enclosing transaction goes on.
+    ///
+    /// Only the trait operations and [`SqlTable::connection`] use the transaction. The handle
+    /// still derefs to the pool, and [`SqlTable::get_pool`] returns it, so queries run on
+    /// either of those commit on their own; custom code should execute on `connection()`.
+    pub fn get_table<Table>(&self) -> SqlTable<P, DB, Table> {
+        let pinned = Pinned {
+            shared: self.shared.clone(),
+            scope: Some(self.finished.clone()),
+        };
+        SqlTable::pinned(self.db.clone(), pinned)
+    }
+
+    /// Nesting depth of this scope: 1 for the transaction itself, 2 and up for savepoints.
+    pub fn depth(&self) -> usize {
+        self.level
+    }
+
+    fn savepoint(&self) -> String {
+        format!("typed_sqlx_client_{}", self.level)
+    }
+}
+
+impl<P: Database, DB> SqlTx<P, DB>
+where
+    for<'c> &'c mut P::Connection: Executor<'c, Database = P>,
+{
+    /// Open a nested scope with `SAVEPOINT`.
+    pub async fn begin(&self) -> Result<SqlTx<P, DB>, sqlx::Error> {
+        let scope = SqlTx {
+            db: self.db.clone(),
+            shared: self.shared.clone(),
+            level: self.level + 1,
+            finished: Arc::new(AtomicBool::new(false)),
+            managed: false,
+        };
+        let sql = format!("SAVEPOINT {}", scope.savepoint());
+        match self.shared.conn.lock().await.as_mut() {
+            Some(PinnedConnection::Transaction(tx)) => {
+                (&mut **tx).execute(sql.as_str()).await?;
+            }
+            _ => return Err(finished()),
+        }
+        Ok(scope)
+    }
+
+    /// Commit the transaction, or release the savepoint of a nested scope.
+    pub async fn commit(self) -> Result<(), sqlx::Error> {
+        self.finish(true).await
+    }
+
+    /// Roll the transaction back, or only this scope when it is nested.
+    /// Dropping the outermost `SqlTx` has the same effect.
+    pub async fn rollback(self) -> Result<(), sqlx::Error> {
+        self.finish(false).await
+    }
+
+    /// Run `f` in a nested scope: released when it returns `Ok`, rolled back when it returns
+    /// `Err`. See [`SqlDB::transaction`] for the outermost level.
+    pub async fn transaction<F, Fut, T, E>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(SqlTx<P, DB>) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: From<sqlx::Error>,
+    {
+        self.begin().await?.run(f).await
+    }
+
+    async fn run<F, Fut, T, E>(self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(SqlTx<P, DB>) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: From<sqlx::Error>,
+    {
+        let handle = SqlTx {
+            db: self.db.clone(),
+            shared: self.shared.clone(),
+            level: self.level,
+            finished: self.finished.clone(),
+            managed: true,
+        };
+        let result = f(handle).await;
+        // The closure may have committed or rolled back the scope itself.
+        if self.finished.load(Ordering::SeqCst) {
+            return result;
+        }
+        match result {
+            Ok(value) => {
+                self.commit().await?;
+                Ok(value)
+            }
+            Err(e) => {
+                self.rollback().await?;
+                Err(e)
+            }
+        }
+    }
+
+    async fn finish(&self, commit: bool) -> Result<(), sqlx::Error> {
+        let mut conn = self.shared.conn.lock().await;
+        if self.finished.swap(true, Ordering::SeqCst) {
+            return Err(finished());
+        }
+        // An abandoned inner scope may have left writes behind; only a rollback removes them.
+        let poisoned = self.shared.abandoned.load(Ordering::SeqCst) > self.level;
+        if poisoned {
+            self.shared.abandoned.store(0, Ordering::SeqCst);
+        }
+        let commit = commit && !poisoned;
+        if self.level == 1 {
+            match conn.take() {
+                Some(PinnedConnection::Transaction(tx)) if commit => tx.commit().await?,
+                Some(PinnedConnection::Transaction(tx)) => tx.rollback().await?,
+                _ => return Err(finished()),
+            }
+        } else {
+            let Some(PinnedConnection::Transaction(tx)) = conn.as_mut() else {
+                return Err(finished());
+            };
+            let savepoint = self.savepoint();
+            if !commit {
+                let sql = format!("ROLLBACK TO SAVEPOINT {}", savepoint);
+                (&mut **tx).execute(sql.as_str()).await?;
+            }
+            let sql = format!("RELEASE SAVEPOINT {}", savepoint);
+            (&mut **tx).execute(sql.as_str()).await?;
+        }
+        if poisoned {
+            return Err(sqlx::Error::InvalidArgument(
+                "a nested transaction scope was dropped without commit or rollback; \
+                 the enclosing scope has been rolled back"
+                    .into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<P: Database, DB> Drop for SqlTx<P, DB> {
+    fn drop(&mut self) {
+        if self.managed || self.finished.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        if self.level == 1 {
+            // Tables may outlive the handle; release the transaction now so sqlx rolls it
+            // back. If an operation holds the connection, that happens when its table is dropped.
+            if let Some(mut conn) = self.shared.conn.try_lock() {
+                conn.take();
+            }
+        } else {
+            // A savepoint cannot be rolled back without awaiting; leave it to the enclosing scope.
+            let level = self.level;
+            let _ =
+                self.shared
+                    .abandoned
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |current| {
+                        Some(if current == 0 {
+                            level
+                        } else {
+                            current.min(level)
+                        })
+                    });
+        }
+    }
+}
+
+/// A single pooled connection that keeps the `DB` marker of its [`SqlDB`].
+///
+/// Tables taken with [`get_table`](Self::get_table) run every operation on this connection
+/// outside of any transaction, which keeps session state such as temporary tables or
+/// `SET` variables visible to all of them. The connection returns to the pool when the
+/// `SqlConn` and its tables are dropped.
+pub struct SqlConn<P: Database, DB> {
+    db: SqlDB<P, DB>,
+    shared: Arc<Shared<P>>,
+}
+
+impl<P: Database, DB> SqlConn<P, DB> {
+    /// Create a table handle whose operations run on this connection.
+    ///
+    /// As with [`SqlTx::get_table`], queries run through the pool (via `Deref` or
+    /// [`SqlTable::get_pool`]) bypass the connection; use [`SqlTable::connection`].
+    pub fn get_table<Table>(&self) -> SqlTable<P, DB, Table> {
+        let pinned = Pinned {
+            shared: self.shared.clone(),
+            scope: None,
+        };
+        SqlTable::pinned(self.db.clone(), pinned)
+    }
+}
+
+impl<P: Database, DB> SqlDB<P, DB> {
+    /// Start a transaction for operations spanning several tables.
+    ///
+    /// See [`SqlTx`] for an example.
+    pub async fn begin(&self) -> Result<SqlTx<P, DB>, sqlx::Error> {
+        let tx = self.pool().begin().await?;
+        Ok(SqlTx {
+            db: self.clone(),
+            shared: Shared::new(PinnedConnection::Transaction(tx)),
+            level: 1,
+            finished: Arc::new(AtomicBool::new(false)),
+            managed: false,
+        })
+    }
+
+    /// Take one connection from the pool for a sequence of operations.
+    pub async fn acquire(&self) -> Result<SqlConn<P, DB>, sqlx::Error> {
+        let conn = self.pool().acquire().await?;
+        Ok(SqlConn {
+            db: self.clone(),
+            shared: Shared::new(PinnedConnection::Connection(conn)),
+        })
+    }
+
+    /// Run `f` in a transaction that is committed when it returns `Ok` and rolled back when
+    /// it returns `Err`.
+    ///
+    /// `f` receives the [`SqlTx`] to take tables from; calling
+    /// [`transaction`](SqlTx::transaction) on it nests a savepoint scope.
+    ///
+    /// ## Example
+    /// ```rust,ignore
+    /// let order_id = db
+    ///     .transaction(|tx| async move {
+    ///         let id = tx.get_table::<Order>().insert(&order).await?;
+    ///         tx.get_table::<Stock>().update_by_id(&item.id, &item).await?;
+    ///         Ok::<_, sqlx::Error>(id)
+    ///     })
+    ///     .await?;
+    /// ```
+    pub async fn transaction<F, Fut, T, E>(&self, f: F) -> Result<T, E>
+    where
+        for<'c> &'c mut P::Connection: Executor<'c, Database = P>,
+        F: FnOnce(SqlTx<P, DB>) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: From<sqlx::Error>,
+    {
+        self.begin().await?.run(f).await
+    }
+}
+
+/// Exclusive access to the connection a [`SqlTable`] operation runs on.
+///
+/// Returned by [`SqlTable::connection`]; dereferences to the backend connection type
+/// (e.g. `sqlx::PgConnection`), so `&mut *conn` can be passed to any sqlx query.
+pub struct TableConnection<'a, P: Database>(ConnectionSource<'a, P>);
+
+enum ConnectionSource<'a, P: Database> {
+    Pool(PoolConnection<P>),
+    Pinned(MutexGuard<'a, Option<PinnedConnection<P>>>),
+}
+
+impl<'a, P: Database> TableConnection<'a, P> {
+    pub(crate) async fn acquire(
+        pool: &Pool<P>,
+        pinned: Option<&'a Pinned<P>>,
+    ) -> Result<Self, sqlx::Error> {
+        let source = match pinned {
+            None => ConnectionSource::Pool(pool.acquire().await?),
+            Some(pinned) => {
+                let guard = pinned.shared.conn.lock().await;
+                let scope_finished = pinned
+                    .scope
+                    .as_ref()
+                    .is_some_and(|scope| scope.load(Ordering::SeqCst));
+                if guard.is_none() || scope_finished {
+                    return Err(finished());
+                }
+                ConnectionSource::Pinned(guard)
+            }
+        };
+        Ok(Self(source))
+    }
+}
+
+impl<P: Database> Deref for TableConnection<'_, P> {
+    type Target = P::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        match &self.0 {
+            ConnectionSource::Pool(conn) => conn,
+            ConnectionSource::Pinned(guard) => match guard.as_ref() {
+                Some(PinnedConnection::Transaction(tx)) => tx,
+                Some(PinnedConnection::Connection(conn)) => conn,
+                None => unreachable!("checked when the connection was acquired"),
+            },
+        }
+    }
+}
+
+impl<P: Database> DerefMut for TableConnection<'_, P> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match &mut self.0 {
+            ConnectionSource::Pool(conn) => conn,
+            ConnectionSource::Pinned(guard) => match guard.as_mut() {
+                Some(PinnedConnection::Transaction(tx)) => tx,
+                Some(PinnedConnection::Connection(conn)) => conn,
+                None => unreachable!("checked when the connection was acquired"),
+            },
+        }
+    }
+}
+
+fn finished() -> sqlx::Error {
+    sqlx::Error::InvalidArgument(
+        "the transaction of this table has already been committed or rolled back".into(),
+    )
+}
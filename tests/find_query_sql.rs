@@ -0,0 +1,188 @@
+//! `FindQuery::to_sql` on each backend: quoting, placeholder numbering and paging.
+
+use sqlx::{FromRow, MySqlPool, PgPool, SqlitePool};
+use typed_sqlx_client::{CrudOpsRef, SqlPool, TableDdl};
+
+struct TestDB;
+
+#[derive(FromRow, CrudOpsRef)]
+#[crud(table = "people", db = "postgres", soft_delete = "deleted_at")]
+#[allow(dead_code)]
+struct PgPerson {
+    #[crud(primary_key)]
+    id: i64,
+    name: String,
+    age: Option<i32>,
+    deleted_at: Option<String>,
+}
+
+#[derive(FromRow, CrudOpsRef)]
+#[crud(table = "people", db = "mysql", soft_delete = "deleted_at")]
+#[allow(dead_code)]
+struct MySqlPerson {
+    #[crud(primary_key)]
+    id: i64,
+    name: String,
+    age: Option<i32>,
+    deleted_at: Option<String>,
+}
+
+#[derive(FromRow, CrudOpsRef, TableDdl, Debug)]
+#[crud(table = "people", db = "sqlite", soft_delete = "deleted_at")]
+struct SqlitePerson {
+    #[crud(primary_key)]
+    id: i64,
+    name: String,
+    age: Option<i32>,
+    deleted_at: Option<String>,
+}
+
+#[tokio::test]
+async fn postgres_numbers_filters_then_paging() {
+    let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+    let people = SqlPool::from_pool::<TestDB>(pool).get_table::<PgPerson>();
+    let c = PgPerson::col;
+
+    let query = people
+        .find()
+        .filter(
+            c.age
+                .ge(Some(18))
+                .and(c.name.like("A%").or(c.age.is_null())),
+        )
+        .filter(c.id.in_list([1, 2, 3]))
+        .order_by_desc(c.age)
+        .order_by(c.name)
+        .limit(10)
+        .offset(20);
+    assert_eq!(
+        query.to_sql(),
+        "SELECT * FROM \"people\" WHERE (\"age\" >= $1 AND (\"name\" LIKE $2 OR \"age\" IS NULL) \
+         AND \"id\" IN ($3, $4, $5)) AND \"deleted_at\" IS NULL \
+         ORDER BY \"age\" DESC, \"name\" ASC LIMIT $6 OFFSET $7"
+    );
+    assert_eq!(
+        people.find().offset(5).to_sql(),
+        "SELECT * FROM \"people\" WHERE \"deleted_at\" IS NULL OFFSET $1"
+    );
+    assert_eq!(
+        people
+            .find()
+            .filter(c.id.eq(1))
+            .limit(1)
+            .with_deleted()
+            .to_sql(),
+        "SELECT * FROM \"people\" WHERE \"id\" = $1 LIMIT $2"
+    );
+    assert_eq!(
+        people
+            .find()
+            .filter(c.id.in_list(Vec::new()))
+            .with_deleted()
+            .to_sql(),
+        "SELECT * FROM \"people\" WHERE 1 = 0"
+    );
+}
+
+#[tokio::test]
+async fn mysql_adds_a_limit_before_a_lone_offset() {
+    let pool = MySqlPool::connect_lazy("mysql://localhost/unused").unwrap();
+    let people = SqlPool::from_pool::<TestDB>(pool).get_table::<MySqlPerson>();
+    let c = MySqlPerson::col;
+
+    assert_eq!(
+        people
+            .find()
+            .filter(c.name.eq("Ann".to_string()).or(c.id.in_list([1, 2])))
+            .offset(5)
+            .to_sql(),
+        "SELECT * FROM `people` WHERE (`name` = ? OR `id` IN (?, ?)) AND `deleted_at` IS NULL \
+         LIMIT 18446744073709551615 OFFSET ?"
+    );
+    assert_eq!(
+        people
+            .find()
+            .filter(c.age.is_not_null())
+            .order_by(c.id)
+            .limit(3)
+            .with_deleted()
+            .to_sql(),
+        "SELECT * FROM `people` WHERE `age` IS NOT NULL ORDER BY `id` ASC LIMIT ?"
+    );
+}
+
+#[tokio::test]
+async fn sqlite_adds_a_limit_before_a_lone_offset() {
+    let pool = SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+    let people = SqlPool::from_pool::<TestDB>(pool).get_table::<SqlitePerson>();
+    let c = SqlitePerson::col;
+
+    assert_eq!(
+        people
+            .find()
+            .filter(c.name.eq("Ann".to_string()).or(c.id.in_list([1, 2])))
+            .offset(5)
+            .to_sql(),
+        "SELECT * FROM \"people\" WHERE (\"name\" = ? OR \"id\" IN (?, ?)) \
+         AND \"deleted_at\" IS NULL LIMIT -1 OFFSET ?"
+    );
+    assert_eq!(
+        people.find().limit(2).offset(1).to_sql(),
+        "SELECT * FROM \"people\" WHERE \"deleted_at\" IS NULL LIMIT ? OFFSET ?"
+    );
+}
+
+#[tokio::test]
+async fn sqlite_binds_filters_before_paging() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let people = SqlPool::from_pool::<TestDB>(pool).get_table::<SqlitePerson>();
+    people.create_table_if_not_exists().await.unwrap();
+    for (id, name, age) in [
+        (1, "Ann", 30),
+        (2, "Bob", 17),
+        (3, "Cid", 45),
+        (4, "Dee", 52),
+    ] {
+        people
+            .insert(&SqlitePerson {
+                id,
+                name: name.to_string(),
+                age: Some(age),
+                deleted_at: None,
+            })
+            .await
+            .unwrap();
+    }
+    people.delete_by_id(&4).await.unwrap();
+    let c = SqlitePerson::col;
+
+    let names = |found: Vec<SqlitePerson>| found.into_iter().map(|p| p.name).collect::<Vec<_>>();
+    let adults = people
+        .find()
+        .filter(c.age.ge(Some(18)).or(c.name.eq("Bob".to_string())))
+        .order_by(c.id)
+        .offset(1)
+        .fetch_all()
+        .await
+        .unwrap();
+    assert_eq!(names(adults), ["Bob", "Cid"]);
+    let page = people
+        .find()
+        .filter(c.id.in_list([1, 2, 3, 4]))
+        .order_by_desc(c.id)
+        .limit(1)
+        .offset(1)
+        .with_deleted()
+        .fetch_all()
+        .await
+        .unwrap();
+    assert_eq!(names(page), ["Cid"]);
+    let count = people
+        .find()
+        .filter(c.age.gt(Some(20)))
+        .limit(1)
+        .count()
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+}
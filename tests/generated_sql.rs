@@ -0,0 +1,180 @@
+//! Checks of the SQL text generated by `#[derive(CrudOpsRef)]`, without a database.
+
+use sqlx::FromRow;
+use typed_sqlx_client::{CrudOpsRef, TableDialect};
+
+#[derive(FromRow, CrudOpsRef)]
+#[crud(table = "events", db = "postgres")]
+#[allow(dead_code)]
+struct PgEvent {
+    #[crud(primary_key)]
+    id: i64,
+    kind: String,
+    source: String,
+    #[crud(created_at)]
+    created_at: Option<String>,
+    payload: String,
+    level: i32,
+    #[crud(updated_at)]
+    updated_at: Option<String>,
+    host: String,
+    tag: Option<String>,
+}
+
+#[derive(FromRow, CrudOpsRef)]
+#[crud(table = "accounts", db = "mysql")]
+#[allow(dead_code)]
+struct MySqlAccount {
+    #[crud(primary_key)]
+    id: i64,
+    email: String,
+    #[crud(version)]
+    version: i64,
+}
+
+fn clock() -> i64 {
+    1_700_000_000
+}
+
+#[derive(FromRow, CrudOpsRef)]
+#[crud(table = "memberships", db = "postgres")]
+#[allow(dead_code)]
+struct PgMembership {
+    #[crud(primary_key)]
+    user_id: i64,
+    #[crud(primary_key)]
+    group_name: String,
+    role: String,
+    note: Option<String>,
+    #[crud(version)]
+    version: i64,
+    #[crud(updated_at = "clock")]
+    updated_at: i64,
+}
+
+#[derive(FromRow, CrudOpsRef)]
+#[crud(table = "events", db = "sqlite")]
+#[allow(dead_code)]
+struct SqliteEvent {
+    #[crud(primary_key)]
+    id: i64,
+    kind: String,
+    #[crud(created_at)]
+    created_at: Option<String>,
+}
+
+#[test]
+fn batch_rows_stay_within_bind_limit() {
+    // Seven bound columns per row; the timestamps are written as SQL.
+    assert_eq!(PgEvent::INSERT_BATCH_ROWS, 65535 / 7);
+    assert_eq!(PgEvent::INSERT_BATCH_ROWS, 9362);
+    assert_eq!(SqliteEvent::INSERT_BATCH_ROWS, 32766 / 2);
+}
+
+#[test]
+fn postgres_values_number_binds_across_rows() {
+    assert_eq!(
+        PgEvent::insert_values_sql(2),
+        "($1, $2, $3, CURRENT_TIMESTAMP, $4, $5, CURRENT_TIMESTAMP, $6, $7), \
+         ($8, $9, $10, CURRENT_TIMESTAMP, $11, $12, CURRENT_TIMESTAMP, $13, $14)"
+    );
+    let last_row = PgEvent::insert_values_sql(PgEvent::INSERT_BATCH_ROWS);
+    assert!(last_row.ends_with(", $65533, $65534)"));
+    assert!(!last_row.contains("$65535"));
+}
+
+#[test]
+fn sqlite_values_repeat_the_row() {
+    assert_eq!(
+        SqliteEvent::insert_values_sql(3),
+        "(?, ?, CURRENT_TIMESTAMP), (?, ?, CURRENT_TIMESTAMP), (?, ?, CURRENT_TIMESTAMP)"
+    );
+}
+
+#[test]
+fn postgres_patch_numbers_clock_and_key_after_values() {
+    let patch = PgMembershipPatch {
+        note: Some(Some("moved".to_string())),
+        ..Default::default()
+    };
+    // Bound in order: patch values, the Rust clock, then the key columns.
+    assert_eq!(
+        PgMembership::update_partial_sql(&patch).unwrap(),
+        "UPDATE \"memberships\" SET \"note\" = $1, \"version\" = \"version\" + 1, \
+         \"updated_at\" = $2 WHERE \"user_id\" = $3 AND \"group_name\" = $4"
+    );
+    let patch = PgMembershipPatch {
+        role: Some("owner".to_string()),
+        note: Some(None),
+    };
+    assert_eq!(
+        PgMembership::update_partial_sql(&patch).unwrap(),
+        "UPDATE \"memberships\" SET \"role\" = $1, \"note\" = $2, \
+         \"version\" = \"version\" + 1, \"updated_at\" = $3 \
+         WHERE \"user_id\" = $4 AND \"group_name\" = $5"
+    );
+}
+
+#[test]
+fn postgres_patch_with_sql_maintained_columns() {
+    let patch = PgEventPatch {
+        kind: Some("deploy".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        PgEvent::update_partial_sql(&patch).unwrap(),
+        "UPDATE \"events\" SET \"kind\" = $1, \"updated_at\" = CURRENT_TIMESTAMP \
+         WHERE \"id\" = $2"
+    );
+    assert_eq!(PgEvent::update_partial_sql(&PgEventPatch::default()), None);
+}
+
+#[test]
+fn table_dialect_follows_the_generated_statements() {
+    type Pg = sqlx::Postgres;
+    assert_eq!(<PgEvent as TableDialect<Pg>>::table_sql(None), "\"events\"");
+    assert_eq!(
+        <PgEvent as TableDialect<Pg>>::table_sql(Some("audit")),
+        "\"audit\".\"events\""
+    );
+    assert_eq!(
+        <PgEvent as TableDialect<Pg>>::quote_ident("kind"),
+        "\"kind\""
+    );
+    assert_eq!(
+        <PgEvent as TableDialect<Pg>>::quote_ident("\"Kind\""),
+        "\"Kind\""
+    );
+    assert_eq!(
+        <PgEvent as TableDialect<Pg>>::quote_ident("a\"b"),
+        "\"a\"\"b\""
+    );
+    assert_eq!(<PgEvent as TableDialect<Pg>>::placeholder(3), "$3");
+    assert_eq!(<PgEvent as TableDialect<Pg>>::unbounded_limit(), None);
+
+    type Lite = sqlx::Sqlite;
+    assert_eq!(<SqliteEvent as TableDialect<Lite>>::placeholder(3), "?");
+    assert_eq!(
+        <SqliteEvent as TableDialect<Lite>>::unbounded_limit(),
+        Some("LIMIT -1")
+    );
+}
+
+#[test]
+fn mysql_insert_ignore_reports_conflicts_as_unaffected() {
+    assert_eq!(
+        MySqlAccount::SQL_INSERT_IGNORE,
+        "INSERT IGNORE INTO `accounts` (`id`, `email`, `version`) VALUES (?, ?, ?)"
+    );
+    assert_eq!(
+        MySqlAccount::SQL_UPSERT,
+        "INSERT INTO `accounts` (`id`, `email`, `version`) VALUES (?, ?, ?) \
+         ON DUPLICATE KEY UPDATE `email` = VALUES(`email`), `version` = `version` + 1"
+    );
+    assert_eq!(
+        PgEvent::SQL_INSERT_IGNORE,
+        "INSERT INTO \"events\" (\"id\", \"kind\", \"source\", \"created_at\", \"payload\", \
+         \"level\", \"updated_at\", \"host\", \"tag\") VALUES ($1, $2, $3, CURRENT_TIMESTAMP, $4, \
+         $5, CURRENT_TIMESTAMP, $6, $7) ON CONFLICT (\"id\") DO NOTHING"
+    );
+}
@@ -0,0 +1,62 @@
+//! `MigrationPlan` against an in-memory SQLite database.
+
+use sqlx::{FromRow, SqlitePool};
+use typed_sqlx_client::{CrudOpsRef, MigrationStep, SqlPool, TableDdl};
+
+struct TestDB;
+
+#[derive(FromRow, CrudOpsRef, TableDdl)]
+#[crud(table = "items", db = "sqlite")]
+#[allow(dead_code)]
+struct ItemV1 {
+    #[crud(primary_key)]
+    id: i64,
+}
+
+#[derive(FromRow, CrudOpsRef, TableDdl)]
+#[crud(table = "items", db = "sqlite")]
+#[allow(dead_code)]
+struct Item {
+    #[crud(primary_key)]
+    id: i64,
+    note: Option<String>,
+    name: String,
+    #[crud(unique)]
+    code: Option<String>,
+}
+
+#[tokio::test]
+async fn risky_column_additions_are_flagged_for_review() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let db = SqlPool::from_pool::<TestDB>(pool);
+    db.get_table::<ItemV1>()
+        .create_table_if_not_exists()
+        .await
+        .unwrap();
+
+    let mut plan = db.migration_plan();
+    plan.table::<Item>().await.unwrap();
+    assert_eq!(
+        plan.steps(),
+        &[
+            MigrationStep::Apply {
+                table: "items".to_string(),
+                sql: r#"ALTER TABLE "items" ADD COLUMN "note" TEXT"#.to_string(),
+            },
+            MigrationStep::Review {
+                table: "items".to_string(),
+                sql: r#"ALTER TABLE "items" ADD COLUMN "name" TEXT NOT NULL"#.to_string(),
+                reason: "adds column name, but SQLite cannot add a NOT NULL column without a \
+                         default"
+                    .to_string(),
+            },
+            MigrationStep::Review {
+                table: "items".to_string(),
+                sql: r#"ALTER TABLE "items" ADD COLUMN "code" TEXT UNIQUE"#.to_string(),
+                reason: "adds column code, but SQLite cannot add a UNIQUE column".to_string(),
+            },
+        ]
+    );
+    assert!(plan.needs_review());
+    assert!(!plan.has_destructive());
+}
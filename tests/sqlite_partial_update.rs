@@ -0,0 +1,72 @@
+//! `update_partial` against an in-memory SQLite database.
+
+use sqlx::{FromRow, SqlitePool};
+use typed_sqlx_client::{CrudOpsRef, PartialUpdateOps, SqlPool, TableDdl};
+
+struct TestDB;
+
+fn clock() -> i64 {
+    1_700_000_000
+}
+
+#[derive(FromRow, CrudOpsRef, TableDdl, Debug, PartialEq)]
+#[crud(table = "memberships", db = "sqlite")]
+struct Membership {
+    #[crud(primary_key)]
+    user_id: i64,
+    #[crud(primary_key)]
+    group_name: String,
+    role: String,
+    note: Option<String>,
+    #[crud(version)]
+    version: i64,
+    #[crud(updated_at = "clock")]
+    updated_at: i64,
+}
+
+#[tokio::test]
+async fn patch_binds_values_clock_then_key() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let table = SqlPool::from_pool::<TestDB>(pool).get_table::<Membership>();
+    table.create_table_if_not_exists().await.unwrap();
+    for group_name in ["admins", "users"] {
+        table
+            .insert(&Membership {
+                user_id: 7,
+                group_name: group_name.to_string(),
+                role: "member".to_string(),
+                note: None,
+                version: 1,
+                updated_at: 0,
+            })
+            .await
+            .unwrap();
+    }
+
+    let patch = MembershipPatch {
+        role: Some("owner".to_string()),
+        note: Some(Some("promoted".to_string())),
+    };
+    let key = (7, "users".to_string());
+    assert_eq!(table.update_partial(&key, &patch).await.unwrap(), 1);
+
+    let updated = table.get_by_id(&key).await.unwrap().unwrap();
+    assert_eq!(
+        updated,
+        Membership {
+            user_id: 7,
+            group_name: "users".to_string(),
+            role: "owner".to_string(),
+            note: Some("promoted".to_string()),
+            version: 2,
+            updated_at: clock(),
+        }
+    );
+    let untouched = table
+        .get_by_id(&(7, "admins".to_string()))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(untouched.role, "member");
+    assert_eq!(untouched.version, 1);
+}
@@ -0,0 +1,55 @@
+//! `SqlDB::with_schema` against an in-memory SQLite database with an attached `staging` one.
+
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::FromRow;
+use typed_sqlx_client::{CrudOpsRef, PartialUpdateOps, SqlPool, TableDdl};
+
+struct TestDB;
+
+#[derive(FromRow, CrudOpsRef, TableDdl, Debug, PartialEq)]
+#[crud(table = "main.notes", db = "sqlite")]
+struct Note {
+    #[crud(primary_key)]
+    id: i64,
+    body: String,
+}
+
+#[tokio::test]
+async fn override_replaces_the_schema_of_a_dotted_table() {
+    // One connection, so the attached database is seen by every query.
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::query("ATTACH DATABASE ':memory:' AS staging")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let main = SqlPool::from_pool::<TestDB>(pool.clone()).get_table::<Note>();
+    let staging = SqlPool::from_pool::<TestDB>(pool)
+        .with_schema("staging")
+        .get_table::<Note>();
+    assert_eq!(main.table_sql(), "\"main\".\"notes\"");
+    assert_eq!(staging.table_sql(), "\"staging\".\"notes\"");
+    assert_eq!(staging.qualified_table_name(), "staging.notes");
+    assert!(staging
+        .create_table_sql()
+        .starts_with("CREATE TABLE \"staging\".\"notes\" ("));
+
+    main.create_table_if_not_exists().await.unwrap();
+    staging.create_table_if_not_exists().await.unwrap();
+    let note = Note {
+        id: 1,
+        body: "draft".to_string(),
+    };
+    staging.insert(&note).await.unwrap();
+    let patch = NotePatch {
+        body: Some("final".to_string()),
+    };
+    assert_eq!(staging.update_partial(&1, &patch).await.unwrap(), 1);
+
+    assert_eq!(main.get_by_id(&1).await.unwrap(), None);
+    let stored = staging.get_by_id(&1).await.unwrap().unwrap();
+    assert_eq!(stored.body, "final");
+}
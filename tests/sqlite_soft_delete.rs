@@ -0,0 +1,131 @@
+//! Soft deletes against an in-memory SQLite database.
+
+use sqlx::{FromRow, SqlitePool};
+use typed_sqlx_client::{CrudOpsRef, NotFound, PartialUpdateOps, SoftDeleteOps, SqlPool, TableDdl};
+
+struct TestDB;
+
+#[derive(FromRow, CrudOpsRef, TableDdl, Debug, PartialEq)]
+#[crud(table = "notes", db = "sqlite", soft_delete = "deleted_at")]
+struct Note {
+    #[crud(primary_key)]
+    id: i64,
+    body: String,
+    deleted_at: Option<String>,
+}
+
+#[derive(FromRow, CrudOpsRef, TableDdl, Debug, PartialEq)]
+#[crud(table = "tasks", db = "sqlite", soft_delete = "deleted_at", strict)]
+struct Task {
+    #[crud(primary_key)]
+    id: i64,
+    deleted_at: Option<String>,
+}
+
+#[tokio::test]
+async fn select_not_deleted_checks_the_projection() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let table = SqlPool::from_pool::<TestDB>(pool).get_table::<Note>();
+    table.create_table_if_not_exists().await.unwrap();
+    for (id, body) in [(1, "kept"), (2, "dropped")] {
+        table
+            .insert(&Note {
+                id,
+                body: body.to_string(),
+                deleted_at: None,
+            })
+            .await
+            .unwrap();
+    }
+    table.delete_by_id(&2).await.unwrap();
+
+    let notes: Vec<Note> = table
+        .execute_select_as_only_not_deleted("SELECT * FROM notes ORDER BY id")
+        .await
+        .unwrap();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].body, "kept");
+
+    let bodies: Vec<(String, Option<String>)> = table
+        .execute_select_as_only_not_deleted("SELECT body, deleted_at FROM notes")
+        .await
+        .unwrap();
+    assert_eq!(bodies, vec![("kept".to_string(), None)]);
+
+    for query in [
+        "SELECT id, body FROM notes",
+        "SELECT deleted_at, deleted_at FROM notes",
+    ] {
+        let error = table
+            .execute_select_as_only_not_deleted::<(i64, String)>(query)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, sqlx::Error::InvalidArgument(_)),
+            "{query}: {error}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn updates_skip_deleted_rows_and_keep_the_stamp() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let table = SqlPool::from_pool::<TestDB>(pool).get_table::<Note>();
+    table.create_table_if_not_exists().await.unwrap();
+    let note = Note {
+        id: 1,
+        body: "draft".to_string(),
+        deleted_at: None,
+    };
+    table.insert(&note).await.unwrap();
+    table.delete_by_id(&1).await.unwrap();
+
+    // The entity's `deleted_at: None` must not resurrect the row.
+    assert_eq!(table.update_by_id(&1, &note).await.unwrap(), 0);
+    let patch = NotePatch {
+        body: Some("edited".to_string()),
+    };
+    assert_eq!(table.update_partial(&1, &patch).await.unwrap(), 0);
+    let deleted = table.get_by_id_with_deleted(&1).await.unwrap().unwrap();
+    assert_eq!(deleted.body, "draft");
+    assert!(deleted.deleted_at.is_some());
+
+    table.restore_by_id(&1).await.unwrap();
+    let stamped = Note {
+        id: 1,
+        body: "final".to_string(),
+        deleted_at: Some("2000-01-01".to_string()),
+    };
+    assert_eq!(table.update_by_id(&1, &stamped).await.unwrap(), 1);
+    let updated = table.get_by_id(&1).await.unwrap().unwrap();
+    assert_eq!(
+        updated,
+        Note {
+            deleted_at: None,
+            ..stamped
+        }
+    );
+}
+
+#[tokio::test]
+async fn strict_restore_and_hard_delete_report_missing_rows() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let table = SqlPool::from_pool::<TestDB>(pool).get_table::<Task>();
+    table.create_table_if_not_exists().await.unwrap();
+    table
+        .insert(&Task {
+            id: 1,
+            deleted_at: None,
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(table.delete_by_id(&1).await.unwrap(), 1);
+    assert_eq!(table.restore_by_id(&1).await.unwrap(), 1);
+    assert_eq!(table.hard_delete_by_id(&1).await.unwrap(), 1);
+
+    let error = table.restore_by_id(&1).await.unwrap_err();
+    assert_eq!(NotFound::from_error(&error).unwrap().id(), "1");
+    let error = table.hard_delete_by_id(&1).await.unwrap_err();
+    assert_eq!(NotFound::from_error(&error).unwrap().table_name(), "tasks");
+}
@@ -0,0 +1,75 @@
+//! Nested transaction scopes against an in-memory SQLite database.
+
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::FromRow;
+use typed_sqlx_client::{CrudOpsRef, SqlPool, TableDdl};
+
+struct TestDB;
+
+#[derive(FromRow, CrudOpsRef, TableDdl, Debug, PartialEq)]
+#[crud(table = "notes", db = "sqlite")]
+struct Note {
+    #[crud(primary_key)]
+    id: i64,
+    body: String,
+}
+
+fn note(id: i64) -> Note {
+    Note {
+        id,
+        body: format!("note {id}"),
+    }
+}
+
+#[tokio::test]
+async fn tables_of_an_ended_scope_are_rejected() {
+    // One connection, so every handle sees the same in-memory database.
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let db = SqlPool::from_pool::<TestDB>(pool);
+    db.get_table::<Note>()
+        .create_table_if_not_exists()
+        .await
+        .unwrap();
+
+    db.transaction(|tx| async move {
+        let released = tx
+            .transaction(|inner| async move {
+                let notes = inner.get_table::<Note>();
+                notes.insert(&note(1)).await?;
+                Ok::<_, sqlx::Error>(notes)
+            })
+            .await?;
+        let error = released.insert(&note(2)).await.unwrap_err();
+        assert!(matches!(error, sqlx::Error::InvalidArgument(_)), "{error}");
+
+        let dropped = tx.begin().await?.get_table::<Note>();
+        let error = dropped.get_by_id(&1).await.unwrap_err();
+        assert!(matches!(error, sqlx::Error::InvalidArgument(_)), "{error}");
+        Ok::<_, sqlx::Error>(())
+    })
+    .await
+    .unwrap_err();
+
+    // The dropped scope rolled the whole transaction back.
+    assert_eq!(db.get_table::<Note>().get_by_id(&1).await.unwrap(), None);
+
+    db.transaction(|tx| async move {
+        let outer = tx.get_table::<Note>();
+        tx.transaction(|inner| async move {
+            inner.get_table::<Note>().insert(&note(1)).await?;
+            Ok::<_, sqlx::Error>(())
+        })
+        .await?;
+        outer.insert(&note(2)).await?;
+        Ok::<_, sqlx::Error>(())
+    })
+    .await
+    .unwrap();
+    let notes = db.get_table::<Note>();
+    assert_eq!(notes.get_by_id(&1).await.unwrap(), Some(note(1)));
+    assert_eq!(notes.get_by_id(&2).await.unwrap(), Some(note(2)));
+}
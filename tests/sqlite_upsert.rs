@@ -0,0 +1,120 @@
+//! Upserts against an in-memory SQLite database.
+
+use sqlx::{FromRow, SqlitePool};
+use typed_sqlx_client::{CrudOpsRef, SqlPool, TableDdl, UpsertOps};
+
+struct TestDB;
+
+#[derive(FromRow, CrudOpsRef, TableDdl, Debug, PartialEq)]
+#[crud(table = "orders", db = "sqlite")]
+struct Order {
+    #[crud(primary_key, generated)]
+    id: Option<i64>,
+    total: f64,
+}
+
+#[derive(FromRow, CrudOpsRef, TableDdl, Debug, PartialEq)]
+#[crud(table = "accounts", db = "sqlite")]
+struct Account {
+    #[crud(primary_key)]
+    id: i64,
+    balance: i64,
+    #[crud(version)]
+    version: i64,
+}
+
+#[tokio::test]
+async fn upsert_on_generated_key_updates_the_row() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let table = SqlPool::from_pool::<TestDB>(pool).get_table::<Order>();
+    table.create_table_if_not_exists().await.unwrap();
+
+    let id = table
+        .insert(&Order {
+            id: None,
+            total: 1.0,
+        })
+        .await
+        .unwrap();
+    let order = Order {
+        id: Some(id),
+        total: 2.5,
+    };
+    table.upsert(&order).await.unwrap();
+    assert!(!table.insert_ignore(&order).await.unwrap());
+    table
+        .upsert_batch(&[
+            Order {
+                id: Some(id),
+                total: 4.0,
+            },
+            Order {
+                id: Some(id + 1),
+                total: 8.0,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let orders = table
+        .find()
+        .order_by(Order::col.id)
+        .fetch_all()
+        .await
+        .unwrap();
+    assert_eq!(
+        orders,
+        vec![
+            Order {
+                id: Some(id),
+                total: 4.0,
+            },
+            Order {
+                id: Some(id + 1),
+                total: 8.0,
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn stale_upsert_bumps_the_version() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let table = SqlPool::from_pool::<TestDB>(pool).get_table::<Account>();
+    table.create_table_if_not_exists().await.unwrap();
+
+    let stale = Account {
+        id: 1,
+        balance: 10,
+        version: 1,
+    };
+    table.insert(&stale).await.unwrap();
+    table
+        .update_by_id(
+            &1,
+            &Account {
+                balance: 20,
+                ..stale
+            },
+        )
+        .await
+        .unwrap();
+    table
+        .upsert(&Account {
+            balance: 30,
+            ..stale
+        })
+        .await
+        .unwrap();
+    assert!(!table.insert_ignore(&stale).await.unwrap());
+
+    let account = table.get_by_id(&1).await.unwrap().unwrap();
+    assert_eq!(
+        account,
+        Account {
+            id: 1,
+            balance: 30,
+            version: 3,
+        }
+    );
+}
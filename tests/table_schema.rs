@@ -0,0 +1,33 @@
+//! `TableSchema` as implemented by `#[derive(CrudOpsRef)]`.
+
+use sqlx::FromRow;
+use typed_sqlx_client::{CrudOpsRef, TableSchema};
+
+#[derive(FromRow, CrudOpsRef)]
+#[crud(table = "orders", db = "sqlite")]
+#[allow(dead_code)]
+struct Order {
+    #[crud(primary_key)]
+    id: i64,
+    #[crud(rename = "total_cents")]
+    total: i64,
+    #[crud(skip)]
+    #[sqlx(skip)]
+    line_count: i32,
+    note: Option<String>,
+}
+
+#[test]
+fn columns_leave_out_skipped_fields() {
+    let columns: Vec<(&str, &str)> = Order::columns()
+        .iter()
+        .map(|column| (column.field, column.column))
+        .collect();
+    assert_eq!(
+        columns,
+        [("id", "id"), ("total", "total_cents"), ("note", "note")]
+    );
+    assert!(Order::column("line_count").is_none());
+    assert!(Order::column("note").unwrap().nullable);
+    assert_eq!(Order::primary_key()[0].field, "id");
+}
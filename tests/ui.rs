@@ -0,0 +1,7 @@
+//! Compile-fail tests for the `#[crud(...)]` attribute diagnostics.
+
+#[test]
+fn attribute_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
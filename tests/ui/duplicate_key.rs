@@ -0,0 +1,13 @@
+use typed_sqlx_client::CrudOpsRef;
+
+#[derive(sqlx::FromRow, CrudOpsRef)]
+#[crud(table = "users")]
+#[crud(table = "people")]
+struct User {
+    #[crud(primary_key)]
+    id: i64,
+    #[crud(rename = "full_name", rename = "display_name")]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use typed_sqlx_client::CrudOpsRef;
+
+#[derive(sqlx::FromRow, CrudOpsRef)]
+#[crud(table = "users", db = "oracle")]
+struct User {
+    #[crud(primary_key)]
+    id: i64,
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use typed_sqlx_client::CrudOpsRef;
+
+#[derive(sqlx::FromRow, CrudOpsRef)]
+#[crud(table = "users", tabel = "people")]
+struct User {
+    #[crud(primary_key, uniq)]
+    id: i64,
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,356 @@
+use crate::dialect::{ColumnValue, Dialect};
+use quote::quote;
+use syn::meta::ParseNestedMeta;
+use syn::spanned::Spanned;
+use syn::{Attribute, LitBool, LitStr, Token};
+
+/// Struct-level `#[crud(...)]` options.
+pub(crate) struct StructAttrs {
+    pub(crate) table: Option<String>,
+    /// Backends to generate impls for, in declaration order.
+    pub(crate) db: Vec<Dialect>,
+    pub(crate) schema: Option<String>,
+    pub(crate) conflict: Option<Vec<String>>,
+    pub(crate) soft_delete: Option<String>,
+    /// Span of the `soft_delete` value, for pointing at a column no field maps to.
+    pub(crate) soft_delete_span: Option<proc_macro2::Span>,
+    /// Zero affected rows on a keyed update or delete is a `NotFound` error.
+    pub(crate) strict: bool,
+}
+
+/// Field-level `#[crud(...)]` options.
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    pub(crate) primary_key: bool,
+    pub(crate) generated: bool,
+    pub(crate) rename: Option<String>,
+    pub(crate) unique: bool,
+    pub(crate) version: bool,
+    pub(crate) skip: bool,
+    pub(crate) insertable: Option<bool>,
+    pub(crate) updatable: Option<bool>,
+    pub(crate) created_at: Option<TimestampSource>,
+    pub(crate) updated_at: Option<TimestampSource>,
+    /// Column type written by `#[derive(TableDdl)]` instead of the default for the Rust type.
+    pub(crate) sql_type: Option<String>,
+}
+
+/// Clock behind a `#[crud(created_at)]` or `#[crud(updated_at)]` column.
+pub(crate) enum TimestampSource {
+    /// `CURRENT_TIMESTAMP`, evaluated by the database.
+    Database,
+    /// A Rust function such as `chrono::Utc::now`, called for every written row.
+    Clock(syn::Path),
+}
+
+impl TimestampSource {
+    pub(crate) fn value(&self) -> ColumnValue {
+        match self {
+            TimestampSource::Database => ColumnValue::Sql("CURRENT_TIMESTAMP"),
+            TimestampSource::Clock(path) => ColumnValue::Bind(quote!(#path())),
+        }
+    }
+}
+
+const STRUCT_KEYS: &str = "`table`, `db`, `schema`, `conflict`, `soft_delete`, `strict`";
+const FIELD_KEYS: &str = "`primary_key`, `generated`, `rename`, `unique`, `version`, `skip`, \
+                          `insertable`, `updatable`, `created_at`, `updated_at`, `sql_type`";
+
+impl StructAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut table = None;
+        let mut table_span = None;
+        let mut db = None;
+        let mut schema = None;
+        let mut conflict = None;
+        let mut soft_delete = None;
+        let mut soft_delete_span = None;
+        let mut strict = false;
+        let mut errors = Errors::default();
+
+        for attr in attrs.iter().filter(|a| a.path().is_ident("crud")) {
+            let result = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("table") {
+                    let lit = parse_name_lit(&meta)?;
+                    table_span = Some(lit.span());
+                    set_once(&meta, &mut table, lit.value())
+                } else if meta.path.is_ident("db") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    set_once(&meta, &mut db, parse_dialects(&lit)?)
+                } else if meta.path.is_ident("schema") {
+                    set_once(&meta, &mut schema, parse_name(&meta)?)
+                } else if meta.path.is_ident("conflict") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    let columns: Vec<String> = lit
+                        .value()
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|c| !c.is_empty())
+                        .map(String::from)
+                        .collect();
+                    if columns.is_empty() {
+                        return Err(syn::Error::new_spanned(
+                            &lit,
+                            "`conflict` needs at least one column name",
+                        ));
+                    }
+                    set_once(&meta, &mut conflict, columns)
+                } else if meta.path.is_ident("soft_delete") {
+                    let lit = parse_name_lit(&meta)?;
+                    soft_delete_span = Some(lit.span());
+                    set_once(&meta, &mut soft_delete, lit.value())
+                } else if meta.path.is_ident("strict") {
+                    set_flag(&meta, &mut strict)
+                } else {
+                    Err(unknown_key(&meta, STRUCT_KEYS))
+                }
+            });
+            errors.push(result);
+        }
+
+        // A dotted table already names its schema; a second one would qualify it twice.
+        if let (Some(table), Some(span), Some(_)) = (&table, table_span, &schema) {
+            if table.contains('.') {
+                errors.push(Err(syn::Error::new(
+                    span,
+                    format!(
+                        "`table = \"{}\"` already names a schema; drop it or the `schema` option",
+                        table
+                    ),
+                )));
+            }
+        }
+        errors.finish()?;
+        Ok(StructAttrs {
+            table,
+            db: db.unwrap_or_else(|| vec![Dialect::MySql]),
+            schema,
+            conflict,
+            soft_delete,
+            soft_delete_span,
+            strict,
+        })
+    }
+}
+
+impl FieldAttrs {
+    pub(crate) fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let mut attrs = FieldAttrs::default();
+        let mut errors = Errors::default();
+        // Spans of the flags that were set, for pointing at conflicts
+        let mut seen: Vec<(&'static str, proc_macro2::Span)> = Vec::new();
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("crud")) {
+            let result = attr.parse_nested_meta(|meta| {
+                let span = meta.path.span();
+                let key: &'static str = if meta.path.is_ident("primary_key") {
+                    set_flag(&meta, &mut attrs.primary_key)?;
+                    "primary_key"
+                } else if meta.path.is_ident("generated") {
+                    set_flag(&meta, &mut attrs.generated)?;
+                    "generated"
+                } else if meta.path.is_ident("unique") {
+                    set_flag(&meta, &mut attrs.unique)?;
+                    "unique"
+                } else if meta.path.is_ident("version") {
+                    set_flag(&meta, &mut attrs.version)?;
+                    "version"
+                } else if meta.path.is_ident("skip") {
+                    set_flag(&meta, &mut attrs.skip)?;
+                    "skip"
+                } else if meta.path.is_ident("rename") {
+                    set_once(&meta, &mut attrs.rename, parse_name(&meta)?)?;
+                    "rename"
+                } else if meta.path.is_ident("insertable") {
+                    let lit: LitBool = meta.value()?.parse()?;
+                    set_once(&meta, &mut attrs.insertable, lit.value)?;
+                    "insertable"
+                } else if meta.path.is_ident("updatable") {
+                    let lit: LitBool = meta.value()?.parse()?;
+                    set_once(&meta, &mut attrs.updatable, lit.value)?;
+                    "updatable"
+                } else if meta.path.is_ident("created_at") {
+                    set_once(&meta, &mut attrs.created_at, parse_timestamp(&meta)?)?;
+                    "created_at"
+                } else if meta.path.is_ident("updated_at") {
+                    set_once(&meta, &mut attrs.updated_at, parse_timestamp(&meta)?)?;
+                    "updated_at"
+                } else if meta.path.is_ident("sql_type") {
+                    set_once(&meta, &mut attrs.sql_type, parse_name(&meta)?)?;
+                    "sql_type"
+                } else {
+                    return Err(unknown_key(&meta, FIELD_KEYS));
+                };
+                seen.push((key, span));
+                Ok(())
+            });
+            errors.push(result);
+        }
+        errors.finish()?;
+
+        // Pairs of options that cannot be combined on one field
+        const CONFLICTS: &[(&str, &str)] = &[
+            ("skip", "primary_key"),
+            ("skip", "generated"),
+            ("skip", "unique"),
+            ("skip", "version"),
+            ("skip", "insertable"),
+            ("skip", "updatable"),
+            ("skip", "created_at"),
+            ("skip", "updated_at"),
+            ("version", "primary_key"),
+            ("version", "created_at"),
+            ("version", "updated_at"),
+            ("created_at", "updated_at"),
+            ("created_at", "primary_key"),
+            ("updated_at", "primary_key"),
+        ];
+        let span_of = |key: &str| seen.iter().find(|(k, _)| *k == key).map(|(_, s)| *s);
+        for (a, b) in CONFLICTS {
+            if let (Some(_), Some(span)) = (span_of(a), span_of(b)) {
+                errors.push(Err(syn::Error::new(
+                    span,
+                    format!("`{}` cannot be combined with `{}`", b, a),
+                )));
+            }
+        }
+        if let Some(span) = span_of("generated") {
+            if !attrs.primary_key {
+                errors.push(Err(syn::Error::new(
+                    span,
+                    "`generated` is only valid together with `primary_key`",
+                )));
+            }
+        }
+        errors.finish()?;
+        Ok(attrs)
+    }
+
+    /// Whether the field is written by generated INSERT statements.
+    pub(crate) fn writes_on_insert(&self) -> bool {
+        !self.skip && self.insertable != Some(false)
+    }
+
+    /// Whether the field is written by generated UPDATE statements.
+    pub(crate) fn writes_on_update(&self) -> bool {
+        !self.skip && self.updatable != Some(false)
+    }
+}
+
+/// Collects every error of an attribute list so they are reported together.
+#[derive(Default)]
+struct Errors(Option<syn::Error>);
+
+impl Errors {
+    fn push(&mut self, result: syn::Result<()>) {
+        if let Err(err) = result {
+            match &mut self.0 {
+                Some(existing) => existing.combine(err),
+                None => self.0 = Some(err),
+            }
+        }
+    }
+
+    fn finish(&mut self) -> syn::Result<()> {
+        match self.0.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The attribute key as written, e.g. `primary_key`.
+fn key_name(meta: &ParseNestedMeta) -> String {
+    meta.path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+fn unknown_key(meta: &ParseNestedMeta, expected: &str) -> syn::Error {
+    meta.error(format!(
+        "unknown `crud` attribute `{}`; expected one of {}",
+        key_name(meta),
+        expected
+    ))
+}
+
+fn set_flag(meta: &ParseNestedMeta, flag: &mut bool) -> syn::Result<()> {
+    if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+        return Err(meta.error(format!("`{}` takes no value", key_name(meta))));
+    }
+    if *flag {
+        return Err(meta.error(format!("duplicate `{}` attribute", key_name(meta))));
+    }
+    *flag = true;
+    Ok(())
+}
+
+fn set_once<T>(meta: &ParseNestedMeta, slot: &mut Option<T>, value: T) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(meta.error(format!("duplicate `{}` attribute", key_name(meta))));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+/// Parse `db = "..."`: one backend, a comma-separated list, or `"all"`.
+fn parse_dialects(lit: &LitStr) -> syn::Result<Vec<Dialect>> {
+    let value = lit.value();
+    if value.trim() == "all" {
+        return Ok(Dialect::ALL.to_vec());
+    }
+    let mut dialects = Vec::new();
+    for name in value.split(',').map(str::trim) {
+        let dialect = Dialect::from_name(name).ok_or_else(|| {
+            syn::Error::new_spanned(
+                lit,
+                format!(
+                    "unknown database `{}`; expected \"mysql\", \"postgres\", \"sqlite\", \
+                     a comma-separated list of them, or \"all\"",
+                    name
+                ),
+            )
+        })?;
+        if dialects.contains(&dialect) {
+            return Err(syn::Error::new_spanned(
+                lit,
+                format!("database `{}` is listed more than once", name),
+            ));
+        }
+        dialects.push(dialect);
+    }
+    Ok(dialects)
+}
+
+/// Parse a non-empty `key = "name"` value.
+fn parse_name(meta: &ParseNestedMeta) -> syn::Result<String> {
+    parse_name_lit(meta).map(|lit| lit.value())
+}
+
+/// Like [`parse_name`], keeping the literal for its span.
+fn parse_name_lit(meta: &ParseNestedMeta) -> syn::Result<LitStr> {
+    let lit: LitStr = meta.value()?.parse()?;
+    if lit.value().trim().is_empty() {
+        return Err(syn::Error::new_spanned(&lit, "expected a non-empty name"));
+    }
+    Ok(lit)
+}
+
+/// Parse `created_at` / `updated_at`, either bare or `= "path::to::clock"`.
+fn parse_timestamp(meta: &ParseNestedMeta) -> syn::Result<TimestampSource> {
+    if !meta.input.peek(Token![=]) {
+        return Ok(TimestampSource::Database);
+    }
+    let lit: LitStr = meta.value()?.parse()?;
+    lit.parse::<syn::Path>()
+        .map(TimestampSource::Clock)
+        .map_err(|_| {
+            syn::Error::new_spanned(
+                &lit,
+                "expected the path of a clock function, e.g. \"chrono::Utc::now\"",
+            )
+        })
+}
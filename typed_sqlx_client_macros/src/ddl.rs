@@ -0,0 +1,303 @@
+use crate::attrs::{FieldAttrs, StructAttrs};
+use crate::dialect::Dialect;
+use crate::{extract_option_inner_type_deep, is_option, resolve_schema, type_name};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use std::collections::HashMap;
+use syn::punctuated::Punctuated;
+use syn::{DeriveInput, Token};
+
+/// Generate the `SQL_CREATE_TABLE` const and the `TableDdl` impl of `input` for one backend.
+pub(crate) fn expand_table_ddl(
+    input: &DeriveInput,
+    fields: &Punctuated<syn::Field, Token![,]>,
+    struct_attrs: &StructAttrs,
+    field_attrs: &HashMap<&syn::Ident, FieldAttrs>,
+    dialect: Dialect,
+    gated: bool,
+) -> syn::Result<TokenStream2> {
+    let struct_name = &input.ident;
+    let table_name = struct_attrs
+        .table
+        .clone()
+        .unwrap_or_else(|| struct_name.to_string());
+    let backend = dialect.backend();
+    let quoted_table = dialect.quote_path(&table_name);
+    let default_sql_table = match &struct_attrs.schema {
+        Some(schema) => format!("{}.{}", dialect.quote_path(schema), quoted_table),
+        None => quoted_table.clone(),
+    };
+    // A runtime schema replaces the `#[crud(schema)]` one or the schema part of a dotted
+    // table, as in `TableDialect::table_sql`, which `TableDdl` does not require.
+    let bare_table = table_name.rsplit('.').next().unwrap_or(&table_name);
+    let quoted_bare_table = dialect.quote_ident(bare_table);
+    let quote_schema = dialect.quote_ident_expr(quote!(schema));
+    let schema_table = quote!(format!("{}.{}", #quote_schema, #quoted_bare_table));
+
+    let attrs_of = |f: &syn::Field| &field_attrs[f.ident.as_ref().unwrap()];
+    let column_name = |f: &syn::Field| {
+        attrs_of(f)
+            .rename
+            .clone()
+            .unwrap_or_else(|| f.ident.as_ref().unwrap().to_string())
+    };
+    // Same default as the CRUD statements: the first field when no key is marked
+    let mut pk_fields: Vec<&syn::Field> =
+        fields.iter().filter(|f| attrs_of(f).primary_key).collect();
+    if pk_fields.is_empty() {
+        pk_fields.push(&fields[0]);
+    }
+    let is_pk = |f: &syn::Field| pk_fields.iter().any(|pk| pk.ident == f.ident);
+    let composite_key = pk_fields.len() > 1;
+
+    // One definition per field: type, NOT NULL unless `Option<T>`, then key constraints
+    let mut definitions = Vec::new();
+    // Per column: name, `ADD COLUMN` statement, type-changing statement and why adding it
+    // needs review
+    let mut alterations = Vec::new();
+    let mut errors: Option<syn::Error> = None;
+    for field in fields.iter().filter(|f| !attrs_of(f).skip) {
+        let attrs = attrs_of(field);
+        let keyed = is_pk(field) || attrs.unique;
+        let inner_ty = extract_option_inner_type_deep(&field.ty);
+        let column_type = match &attrs.sql_type {
+            Some(sql_type) => sql_type.as_str(),
+            None => match dialect.column_type(inner_ty, keyed) {
+                Some(column_type) => column_type,
+                None => {
+                    let e = syn::Error::new_spanned(
+                        &field.ty,
+                        format!(
+                            "no default {} column type for `{}`; set one with \
+                             `#[crud(sql_type = \"...\")]`",
+                            dialect.name(),
+                            type_name(inner_ty)
+                        ),
+                    );
+                    match &mut errors {
+                        Some(existing) => existing.combine(e),
+                        None => errors = Some(e),
+                    }
+                    continue;
+                }
+            },
+        };
+        let mut definition = format!(
+            "{} {}",
+            dialect.quote_ident(&column_name(field)),
+            column_type
+        );
+        if !is_option(&field.ty) {
+            definition.push_str(" NOT NULL");
+        }
+        if attrs.generated {
+            definition.push_str(dialect.auto_increment(column_type).unwrap_or(""));
+        }
+        let alter_type = dialect.alter_column_type(
+            &default_sql_table,
+            &dialect.quote_ident(&column_name(field)),
+            column_type,
+            &definition,
+        );
+        if is_pk(field) && !composite_key {
+            definition.push_str(" PRIMARY KEY");
+        } else if attrs.unique && !is_pk(field) {
+            definition.push_str(" UNIQUE");
+        }
+        let caveat = if is_pk(field) {
+            Some("it is part of the primary key, which existing rows have no value for")
+        } else if attrs.unique && dialect == Dialect::Sqlite {
+            Some("SQLite cannot add a UNIQUE column")
+        } else if !is_option(&field.ty) && dialect == Dialect::Sqlite {
+            Some("SQLite cannot add a NOT NULL column without a default")
+        } else if !is_option(&field.ty) {
+            Some("it is NOT NULL without a default, which fails on a table with rows")
+        } else {
+            None
+        };
+        alterations.push((
+            column_name(field),
+            format!(
+                "ALTER TABLE {} ADD COLUMN {}",
+                default_sql_table, definition
+            ),
+            alter_type,
+            caveat,
+        ));
+        definitions.push(definition);
+    }
+    if let Some(e) = errors {
+        return Err(e);
+    }
+
+    let pk_names: Vec<String> = pk_fields.iter().map(|f| column_name(f)).collect();
+    let quote_list = |names: &[String]| {
+        names
+            .iter()
+            .map(|name| dialect.quote_ident(name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if composite_key {
+        definitions.push(format!("PRIMARY KEY ({})", quote_list(&pk_names)));
+    }
+    // Upserts need a unique index on the conflict target unless it is the key or a unique field
+    if let Some(conflict) = &struct_attrs.conflict {
+        let mut sorted_conflict = conflict.clone();
+        sorted_conflict.sort();
+        let mut sorted_pk = pk_names.clone();
+        sorted_pk.sort();
+        let single_unique = conflict.len() == 1
+            && fields
+                .iter()
+                .any(|f| attrs_of(f).unique && column_name(f) == conflict[0]);
+        if sorted_conflict != sorted_pk && !single_unique {
+            definitions.push(format!("UNIQUE ({})", quote_list(conflict)));
+        }
+    }
+
+    let create_sql = format!(
+        "CREATE TABLE {} (\n    {}\n)",
+        default_sql_table,
+        definitions.join(",\n    ")
+    );
+    let create_if_not_exists_sql =
+        create_sql.replacen("CREATE TABLE ", "CREATE TABLE IF NOT EXISTS ", 1);
+    let drop_sql = format!("DROP TABLE IF EXISTS {}", default_sql_table);
+
+    let suffix = if gated { dialect.const_suffix() } else { "" };
+    let sql_create_table = format_ident!("SQL_CREATE_TABLE{}", suffix);
+    let create_doc = format!(
+        "{} statement creating the table, as returned by `create_table_sql`.",
+        dialect.name()
+    );
+    let create_sql_expr = resolve_schema(
+        &schema_table,
+        &default_sql_table,
+        &create_sql,
+        quote!(#struct_name::#sql_create_table),
+    );
+    let create_if_not_exists_expr = resolve_schema(
+        &schema_table,
+        &default_sql_table,
+        &create_if_not_exists_sql,
+        quote!(#create_if_not_exists_sql),
+    );
+    let drop_expr = resolve_schema(
+        &schema_table,
+        &default_sql_table,
+        &drop_sql,
+        quote!(#drop_sql),
+    );
+    let alter_expr = resolve_schema(
+        &schema_table,
+        &default_sql_table,
+        &format!("ALTER TABLE {}", default_sql_table),
+        quote!(sql),
+    );
+    let drop_column_sql = format!("ALTER TABLE {} DROP COLUMN ", default_sql_table);
+    let drop_column_expr = resolve_schema(
+        &schema_table,
+        &default_sql_table,
+        &drop_column_sql,
+        quote!(#drop_column_sql),
+    );
+    let quote_column = dialect.quote_ident_expr(quote!(column));
+    let alter_columns: Vec<&String> = alterations.iter().map(|(column, ..)| column).collect();
+    let add_columns: Vec<&String> = alterations.iter().map(|(_, add, ..)| add).collect();
+    let type_arms: Vec<TokenStream2> = alterations
+        .iter()
+        .filter_map(|(column, _, alter_type, _)| {
+            alter_type.as_ref().map(|sql| quote!(#column => #sql,))
+        })
+        .collect();
+    let caveat_arms: Vec<TokenStream2> = alterations
+        .iter()
+        .filter_map(|(column, _, _, caveat)| caveat.map(|caveat| quote!(#column => Some(#caveat),)))
+        .collect();
+    let caveat_body = if caveat_arms.is_empty() {
+        quote! {
+            let _ = column;
+            None
+        }
+    } else {
+        quote! {
+            match column {
+                #( #caveat_arms )*
+                _ => None,
+            }
+        }
+    };
+    let alter_type_body = if type_arms.is_empty() {
+        quote! {
+            let _ = column;
+            None
+        }
+    } else {
+        quote! {
+            let sql = match column {
+                #( #type_arms )*
+                _ => return None,
+            };
+            Some(#alter_expr.into_owned())
+        }
+    };
+
+    Ok(quote! {
+        impl #struct_name {
+            #[doc = #create_doc]
+            pub const #sql_create_table: &'static str = #create_sql;
+        }
+
+        impl<DB> typed_sqlx_client::TableDdl<#struct_name>
+            for typed_sqlx_client::SqlTable<#backend, DB, #struct_name>
+        where
+            DB: Send + Sync,
+            for<'c> &'c mut <#backend as sqlx::Database>::Connection: sqlx::Executor<'c, Database = #backend>,
+        {
+            type Error = sqlx::Error;
+
+            fn create_table_sql(&self) -> String {
+                #create_sql_expr.into_owned()
+            }
+
+            fn create_table_if_not_exists(&self) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
+                async move {
+                    let sql = #create_if_not_exists_expr;
+                    let mut conn = self.connection().await?;
+                    sqlx::query(&sql).execute(&mut *conn).await?;
+                    Ok(())
+                }
+            }
+
+            fn drop_table(&self) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
+                async move {
+                    let sql = #drop_expr;
+                    let mut conn = self.connection().await?;
+                    sqlx::query(&sql).execute(&mut *conn).await?;
+                    Ok(())
+                }
+            }
+
+            fn add_column_sql(&self, column: &str) -> Option<String> {
+                let sql = match column {
+                    #( #alter_columns => #add_columns, )*
+                    _ => return None,
+                };
+                Some(#alter_expr.into_owned())
+            }
+
+            fn add_column_caveat(&self, column: &str) -> Option<&'static str> {
+                #caveat_body
+            }
+
+            fn alter_column_type_sql(&self, column: &str) -> Option<String> {
+                #alter_type_body
+            }
+
+            fn drop_column_sql(&self, column: &str) -> String {
+                format!("{}{}", #drop_column_expr, #quote_column)
+            }
+        }
+    })
+}
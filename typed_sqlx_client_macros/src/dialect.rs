@@ -0,0 +1,427 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// SQL backend targeted by a `#[derive(CrudOpsRef)]` expansion.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Dialect {
+    MySql,
+    Postgres,
+    Sqlite,
+}
+
+impl Dialect {
+    /// Every supported backend, as generated by `db = "all"`.
+    pub(crate) const ALL: [Dialect; 3] = [Dialect::MySql, Dialect::Postgres, Dialect::Sqlite];
+
+    /// Map a `db = "..."` value to a dialect.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "mysql" => Some(Dialect::MySql),
+            "postgres" => Some(Dialect::Postgres),
+            "sqlite" => Some(Dialect::Sqlite),
+            _ => None,
+        }
+    }
+
+    /// Display name of the backend, for docs and error messages.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Dialect::MySql => "MySQL",
+            Dialect::Postgres => "PostgreSQL",
+            Dialect::Sqlite => "SQLite",
+        }
+    }
+
+    /// Suffix of the `SQL_*` consts when one struct derives for several backends.
+    pub(crate) fn const_suffix(self) -> &'static str {
+        match self {
+            Dialect::MySql => "_MYSQL",
+            Dialect::Postgres => "_POSTGRES",
+            Dialect::Sqlite => "_SQLITE",
+        }
+    }
+
+    /// Wrap `tokens` so they only compile when `typed_sqlx_client` has this backend's feature.
+    pub(crate) fn feature_gate(self, tokens: TokenStream) -> TokenStream {
+        let gate = match self {
+            Dialect::MySql => quote!(__cfg_mysql),
+            Dialect::Postgres => quote!(__cfg_postgres),
+            Dialect::Sqlite => quote!(__cfg_sqlite),
+        };
+        quote!(typed_sqlx_client::#gate! { #tokens })
+    }
+
+    /// The sqlx database type, e.g. `sqlx::Postgres`.
+    pub(crate) fn backend(self) -> TokenStream {
+        match self {
+            Dialect::MySql => quote!(sqlx::MySql),
+            Dialect::Postgres => quote!(sqlx::Postgres),
+            Dialect::Sqlite => quote!(sqlx::Sqlite),
+        }
+    }
+
+    /// The sqlx row type, e.g. `sqlx::postgres::PgRow`.
+    pub(crate) fn row(self) -> TokenStream {
+        match self {
+            Dialect::MySql => quote!(sqlx::mysql::MySqlRow),
+            Dialect::Postgres => quote!(sqlx::postgres::PgRow),
+            Dialect::Sqlite => quote!(sqlx::sqlite::SqliteRow),
+        }
+    }
+
+    /// Where-clause predicate requiring the backend arguments to be usable as query arguments.
+    pub(crate) fn arguments_bound(self) -> TokenStream {
+        match self {
+            Dialect::MySql => {
+                quote!(sqlx::mysql::MySqlArguments: for<'q> sqlx::IntoArguments<'q, sqlx::MySql>)
+            }
+            Dialect::Postgres => {
+                quote!(sqlx::postgres::PgArguments: for<'q> sqlx::IntoArguments<'q, sqlx::Postgres>)
+            }
+            Dialect::Sqlite => {
+                quote!(for<'q> sqlx::sqlite::SqliteArguments<'q>: sqlx::IntoArguments<'q, sqlx::Sqlite>)
+            }
+        }
+    }
+
+    /// Bind placeholder for the 1-based parameter `index`.
+    pub(crate) fn placeholder(self, index: usize) -> String {
+        match self {
+            Dialect::Postgres => format!("${}", index),
+            Dialect::MySql | Dialect::Sqlite => "?".to_string(),
+        }
+    }
+
+    /// Quote a single identifier: backticks on MySQL, double quotes elsewhere.
+    ///
+    /// Embedded quote characters are doubled; an identifier already wrapped in the
+    /// dialect's quotes is returned unchanged.
+    pub(crate) fn quote_ident(self, name: &str) -> String {
+        let quote = match self {
+            Dialect::MySql => '`',
+            Dialect::Postgres | Dialect::Sqlite => '"',
+        };
+        if name.len() >= 2 && name.starts_with(quote) && name.ends_with(quote) {
+            return name.to_string();
+        }
+        let escaped = name.replace(quote, &format!("{0}{0}", quote));
+        format!("{0}{1}{0}", quote, escaped)
+    }
+
+    /// Expression quoting the runtime `&str` identifier `name` like [`quote_ident`](Self::quote_ident).
+    pub(crate) fn quote_ident_expr(self, name: TokenStream) -> TokenStream {
+        let quote = match self {
+            Dialect::MySql => '`',
+            Dialect::Postgres | Dialect::Sqlite => '"',
+        };
+        let doubled = format!("{0}{0}", quote);
+        quote! {{
+            let name: &str = #name;
+            if name.len() >= 2 && name.starts_with(#quote) && name.ends_with(#quote) {
+                name.to_string()
+            } else {
+                format!("{0}{1}{0}", #quote, name.replace(#quote, #doubled))
+            }
+        }}
+    }
+
+    /// Quote a possibly schema-qualified name such as `billing.invoices` part by part.
+    pub(crate) fn quote_path(self, name: &str) -> String {
+        name.split('.')
+            .map(|part| self.quote_ident(part))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// The comma-separated values of a single-row `VALUES (...)` clause.
+    pub(crate) fn row_values(self, values: &[ColumnValue]) -> String {
+        let mut next = 1;
+        values
+            .iter()
+            .map(|value| value.sql(self, &mut next))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Expression building the `(..), (..)` list of a multi-row `VALUES` clause.
+    ///
+    /// `rows` is evaluated at runtime; `values` describes one row.
+    pub(crate) fn values_list(self, rows: TokenStream, values: &[ColumnValue]) -> TokenStream {
+        match self {
+            Dialect::Postgres => {
+                let binds = values.iter().filter(|v| v.bind().is_some()).count();
+                let mut next = 1usize;
+                let parts: Vec<_> = values
+                    .iter()
+                    .map(|value| match value {
+                        ColumnValue::Bind(_) => {
+                            let col = next;
+                            next += 1;
+                            quote!(format!("${}", row * #binds + #col))
+                        }
+                        ColumnValue::Sql(sql) => quote!(String::from(#sql)),
+                    })
+                    .collect();
+                quote! {
+                    (0..#rows)
+                        .map(|row| {
+                            let placeholders: Vec<String> = vec![#(#parts),*];
+                            format!("({})", placeholders.join(", "))
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                }
+            }
+            Dialect::MySql | Dialect::Sqlite => {
+                let row = format!("({})", self.row_values(values));
+                quote!(vec![#row; #rows].join(", "))
+            }
+        }
+    }
+
+    /// The `LIMIT` clause required before an `OFFSET` that should not limit the rows, on
+    /// backends that only accept `OFFSET` after a `LIMIT`.
+    pub(crate) fn unbounded_limit(self) -> Option<&'static str> {
+        match self {
+            Dialect::MySql => Some("LIMIT 18446744073709551615"),
+            Dialect::Postgres => None,
+            Dialect::Sqlite => Some("LIMIT -1"),
+        }
+    }
+
+    /// Whether `INSERT ... RETURNING` is available.
+    pub(crate) fn supports_returning(self) -> bool {
+        matches!(self, Dialect::Postgres | Dialect::Sqlite)
+    }
+
+    /// Column type for a field of Rust type `ty` (without `Option`), or `None` if the type has
+    /// no default and needs `#[crud(sql_type = "...")]`.
+    ///
+    /// `keyed` columns are part of the primary key or unique; MySQL cannot index `TEXT` or
+    /// `BLOB` without a prefix length, so those get `VARCHAR(255)` / `VARBINARY(255)`.
+    pub(crate) fn column_type(self, ty: &syn::Type, keyed: bool) -> Option<&'static str> {
+        let syn::Type::Path(type_path) = ty else {
+            return None;
+        };
+        let segment = type_path.path.segments.last()?;
+        let name = match segment.ident.to_string().as_str() {
+            "Vec" if is_vec_u8(segment) => "Vec<u8>",
+            "Value" if type_path.path.segments.first()?.ident != "serde_json" => return None,
+            "Value" | "JsonValue" => "Json",
+            other => COLUMN_TYPES.iter().find(|t| t.0 == other)?.0,
+        };
+        if keyed && self == Dialect::MySql {
+            match name {
+                "String" => return Some("VARCHAR(255)"),
+                "Vec<u8>" => return Some("VARBINARY(255)"),
+                _ => {}
+            }
+        }
+        let (_, mysql, postgres, sqlite) = COLUMN_TYPES.iter().find(|t| t.0 == name)?;
+        match self {
+            Dialect::MySql => *mysql,
+            Dialect::Postgres => *postgres,
+            Dialect::Sqlite => *sqlite,
+        }
+    }
+
+    /// Column options making an integer key of type `column_type` assigned by the database.
+    ///
+    /// Empty for SQLite, where an `INTEGER PRIMARY KEY` column already is the rowid.
+    pub(crate) fn auto_increment(self, column_type: &str) -> Option<&'static str> {
+        let integer = matches!(
+            column_type,
+            "SMALLINT"
+                | "INT"
+                | "INTEGER"
+                | "BIGINT"
+                | "SMALLINT UNSIGNED"
+                | "INT UNSIGNED"
+                | "BIGINT UNSIGNED"
+        );
+        if !integer {
+            return None;
+        }
+        Some(match self {
+            Dialect::MySql => " AUTO_INCREMENT",
+            Dialect::Postgres => " GENERATED BY DEFAULT AS IDENTITY",
+            Dialect::Sqlite => "",
+        })
+    }
+
+    /// Statement changing the type of a column of `table` to `column_type`, given the quoted
+    /// column and its definition without key constraints.
+    ///
+    /// `None` for SQLite, which cannot change a column's type without rebuilding the table.
+    pub(crate) fn alter_column_type(
+        self,
+        table: &str,
+        column: &str,
+        column_type: &str,
+        definition: &str,
+    ) -> Option<String> {
+        match self {
+            Dialect::MySql => Some(format!(
+                "ALTER TABLE {} MODIFY COLUMN {}",
+                table, definition
+            )),
+            Dialect::Postgres => Some(format!(
+                "ALTER TABLE {} ALTER COLUMN {} TYPE {}",
+                table, column, column_type
+            )),
+            Dialect::Sqlite => None,
+        }
+    }
+
+    /// Query listing a table's columns as `(name, nullable, has_default)` rows, the flags
+    /// being `0` or `1`. It binds the schema (`NULL` for the connection's default) and then
+    /// the table name, and returns no rows if the table does not exist.
+    pub(crate) fn columns_query(self) -> &'static str {
+        match self {
+            Dialect::MySql => {
+                "SELECT CAST(COLUMN_NAME AS CHAR), \
+                 CASE WHEN IS_NULLABLE = 'YES' THEN 1 ELSE 0 END, \
+                 CASE WHEN COLUMN_DEFAULT IS NOT NULL OR EXTRA LIKE '%auto_increment%' \
+                 OR EXTRA LIKE '%GENERATED%' THEN 1 ELSE 0 END \
+                 FROM information_schema.COLUMNS \
+                 WHERE TABLE_SCHEMA = COALESCE(?, DATABASE()) AND TABLE_NAME = ? \
+                 ORDER BY ORDINAL_POSITION"
+            }
+            Dialect::Postgres => {
+                "SELECT CAST(column_name AS TEXT), \
+                 CAST(CASE WHEN is_nullable = 'YES' THEN 1 ELSE 0 END AS BIGINT), \
+                 CAST(CASE WHEN column_default IS NOT NULL OR is_identity = 'YES' \
+                 OR is_generated <> 'NEVER' THEN 1 ELSE 0 END AS BIGINT) \
+                 FROM information_schema.columns \
+                 WHERE table_schema = COALESCE($1, current_schema()) AND table_name = $2 \
+                 ORDER BY ordinal_position"
+            }
+            Dialect::Sqlite => {
+                "SELECT name, CASE WHEN \"notnull\" = 0 THEN 1 ELSE 0 END, \
+                 CASE WHEN dflt_value IS NOT NULL OR (pk = 1 AND upper(type) = 'INTEGER') \
+                 THEN 1 ELSE 0 END \
+                 FROM pragma_table_info(?2, COALESCE(?1, 'main')) ORDER BY cid"
+            }
+        }
+    }
+
+    /// Rows per multi-row statement binding `binds_per_row` values each, keeping every
+    /// statement within [`max_bind_params`](Self::max_bind_params).
+    pub(crate) fn rows_per_statement(self, binds_per_row: usize) -> usize {
+        (self.max_bind_params() / binds_per_row.max(1)).max(1)
+    }
+
+    /// Maximum number of bind parameters accepted in a single statement.
+    ///
+    /// Postgres and MySQL use a 16-bit parameter count on the wire. For SQLite this is
+    /// the default `SQLITE_MAX_VARIABLE_NUMBER` of the bundled library (3.32.0+).
+    pub(crate) fn max_bind_params(self) -> usize {
+        match self {
+            Dialect::MySql | Dialect::Postgres => 65535,
+            Dialect::Sqlite => 32766,
+        }
+    }
+}
+
+/// A Rust type, by the last segment of its path, and its MySQL, PostgreSQL and SQLite column types.
+type ColumnTypes = (
+    &'static str,
+    Option<&'static str>,
+    Option<&'static str>,
+    Option<&'static str>,
+);
+
+/// Default column types for `#[derive(TableDdl)]`.
+const COLUMN_TYPES: &[ColumnTypes] = &[
+    ("bool", Some("BOOLEAN"), Some("BOOLEAN"), Some("BOOLEAN")),
+    ("i8", Some("TINYINT"), None, Some("INTEGER")),
+    ("i16", Some("SMALLINT"), Some("SMALLINT"), Some("INTEGER")),
+    ("i32", Some("INT"), Some("INTEGER"), Some("INTEGER")),
+    ("i64", Some("BIGINT"), Some("BIGINT"), Some("INTEGER")),
+    ("u8", Some("TINYINT UNSIGNED"), None, Some("INTEGER")),
+    ("u16", Some("SMALLINT UNSIGNED"), None, Some("INTEGER")),
+    ("u32", Some("INT UNSIGNED"), None, Some("INTEGER")),
+    ("u64", Some("BIGINT UNSIGNED"), None, None),
+    ("f32", Some("FLOAT"), Some("REAL"), Some("REAL")),
+    (
+        "f64",
+        Some("DOUBLE"),
+        Some("DOUBLE PRECISION"),
+        Some("REAL"),
+    ),
+    ("String", Some("TEXT"), Some("TEXT"), Some("TEXT")),
+    ("Vec<u8>", Some("BLOB"), Some("BYTEA"), Some("BLOB")),
+    ("Uuid", Some("BINARY(16)"), Some("UUID"), Some("BLOB")),
+    ("NaiveDate", Some("DATE"), Some("DATE"), Some("DATE")),
+    ("NaiveTime", Some("TIME"), Some("TIME"), Some("TIME")),
+    (
+        "NaiveDateTime",
+        Some("DATETIME"),
+        Some("TIMESTAMP"),
+        Some("DATETIME"),
+    ),
+    (
+        "DateTime",
+        Some("DATETIME"),
+        Some("TIMESTAMPTZ"),
+        Some("DATETIME"),
+    ),
+    ("Date", Some("DATE"), Some("DATE"), Some("DATE")),
+    ("Time", Some("TIME"), Some("TIME"), Some("TIME")),
+    (
+        "PrimitiveDateTime",
+        Some("DATETIME"),
+        Some("TIMESTAMP"),
+        Some("DATETIME"),
+    ),
+    (
+        "OffsetDateTime",
+        Some("DATETIME"),
+        Some("TIMESTAMPTZ"),
+        Some("DATETIME"),
+    ),
+    ("Decimal", None, Some("NUMERIC"), None),
+    ("BigDecimal", None, Some("NUMERIC"), None),
+    ("Json", Some("JSON"), Some("JSONB"), Some("TEXT")),
+];
+
+/// Whether `segment` is `Vec<u8>`.
+fn is_vec_u8(segment: &syn::PathSegment) -> bool {
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => matches!(
+            args.args.first(),
+            Some(syn::GenericArgument::Type(syn::Type::Path(inner))) if inner.path.is_ident("u8")
+        ),
+        _ => false,
+    }
+}
+
+/// How a column gets its value in a generated `INSERT` or `UPDATE`.
+pub(crate) enum ColumnValue {
+    /// A bound parameter; the expression is passed to `query.bind(..)`.
+    Bind(TokenStream),
+    /// An SQL expression written into the statement, e.g. `CURRENT_TIMESTAMP`.
+    Sql(&'static str),
+}
+
+impl ColumnValue {
+    /// The expression to bind, if this column takes a parameter.
+    pub(crate) fn bind(&self) -> Option<TokenStream> {
+        match self {
+            ColumnValue::Bind(expr) => Some(expr.clone()),
+            ColumnValue::Sql(_) => None,
+        }
+    }
+
+    /// The SQL for this value, consuming the placeholder `next` if it is bound.
+    pub(crate) fn sql(&self, dialect: Dialect, next: &mut usize) -> String {
+        match self {
+            ColumnValue::Bind(_) => {
+                let placeholder = dialect.placeholder(*next);
+                *next += 1;
+                placeholder
+            }
+            ColumnValue::Sql(sql) => sql.to_string(),
+        }
+    }
+}
@@ -1,6 +1,15 @@
+mod attrs;
+mod ddl;
+mod dialect;
+
+use attrs::{FieldAttrs, StructAttrs};
+use dialect::{ColumnValue, Dialect};
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, Meta};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use std::collections::HashMap;
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Token};
 
 /// Derive macro for automatically implementing the CrudOpsRef trait.
 ///
@@ -35,21 +44,128 @@ use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, Meta};
 /// #[crud(table = "table_name")]          // Specify table name (defaults to struct name)
 /// #[crud(db = "database_type")]           // Specify database type (mysql/postgres/sqlite)
 /// #[crud(table = "users", db = "postgres")]  // Combined syntax
+/// #[crud(db = "postgres, sqlite")]        // Generate impls for several backends ("all" for every one)
+/// #[crud(conflict = "tenant_id,email")]  // Conflict target for upserts (column names)
+/// #[crud(soft_delete = "deleted_at")]    // delete_by_id stamps this column, get_by_id skips stamped rows
+/// #[crud(schema = "billing")]            // Qualify the table (Postgres schema / MySQL database)
+/// #[crud(strict)]                        // Updates/deletes/restores matching no row fail with NotFound
 /// ```
 ///
 /// ### Field-level Attributes
 /// ```rust
-/// #[crud(primary_key)]                   // Mark field as primary key (defaults to first field)
+/// #[crud(primary_key)]                   // Mark field as primary key (defaults to first field);
+///                                        // mark several fields for a composite key
+/// #[crud(primary_key, generated)]        // Key assigned by the database (auto-increment, DEFAULT):
+///                                        // left out of INSERT and returned by insert/insert_batch
 /// #[crud(rename = "column_name")]         // Map field to different column name
+/// #[crud(unique)]                        // Use as upsert conflict target instead of the primary key
+/// #[crud(skip)]                          // Never written (computed, joined or `#[sqlx(skip)]` fields)
+/// #[crud(insertable = false)]            // Left out of INSERT statements
+/// #[crud(updatable = false)]             // Left out of UPDATE statements, upsert updates and `Patch`
+/// #[crud(version)]                       // Optimistic-locking counter: checked and bumped by updates
+/// #[crud(created_at)]                    // Set to CURRENT_TIMESTAMP on insert, never updated
+/// #[crud(updated_at)]                    // Set to CURRENT_TIMESTAMP on insert and every update
+/// #[crud(updated_at = "chrono::Utc::now")] // Same, but the value comes from a Rust clock function
+/// #[crud(sql_type = "VARCHAR(42)")]      // Column type used by `#[derive(TableDdl)]`
+/// ```
+///
+/// Table and column names are quoted in every generated statement (backticks on MySQL,
+/// double quotes on PostgreSQL/SQLite), so reserved words such as `order` or `group` and
+/// mixed-case names work as written. A dotted table name like `"billing.invoices"` is quoted
+/// part by part and cannot be combined with `schema`. With `#[crud(schema = "...")]` the
+/// table is qualified with that schema; `SqlDB::with_schema` overrides it, or the schema part
+/// of a dotted name, at runtime. `table_name()` always returns the name as declared and
+/// `qualified_table_name()` the one used in SQL.
+///
+/// ### Several Backends
+/// `db` accepts a comma-separated list or `"all"`. One definition then yields impls for
+/// `SqlTable<sqlx::Postgres, _, T>`, `SqlTable<sqlx::Sqlite, _, T>` and so on. Each
+/// backend's impls are only compiled when the matching `typed_sqlx_client` feature
+/// (`mysql`, `postgres`, `sqlite`) is enabled:
+///
+/// ```toml
+/// typed_sqlx_client = { version = "0.2", features = ["postgres", "sqlite"] }
 /// ```
 ///
 /// ## 🔧 Generated Operations
 /// The macro implements these methods on `SqlTable<P, DB, YourStruct>`:
-/// - `insert(&self, entity: &T) -> Result<(), sqlx::Error>`
-/// - `insert_batch(&self, entities: &[T]) -> Result<(), sqlx::Error>`
+/// - `insert(&self, entity: &T) -> Result<(), sqlx::Error>` (or `Result<ID, _>` with a generated key)
+/// - `insert_batch(&self, entities: &[T]) -> Result<(), sqlx::Error>` (multi-row `INSERT ... VALUES`, in one transaction;
+///   `Result<Vec<ID>, _>` with a generated key)
+/// - `insert_batch_best_effort(&self, entities: &[T]) -> Result<BatchInsertReport<sqlx::Error>, sqlx::Error>`
+/// - `insert_returning(&self, entity: &T) -> Result<T, sqlx::Error>`
 /// - `get_by_id(&self, id: &ID) -> Result<Option<T>, sqlx::Error>`
-/// - `update_by_id(&self, id: &ID, entity: &T) -> Result<(), sqlx::Error>`
-/// - `delete_by_id(&self, id: &ID) -> Result<(), sqlx::Error>`
+/// - `update_by_id(&self, id: &ID, entity: &T) -> Result<u64, sqlx::Error>` (rows matched)
+/// - `update_by_id_returning(&self, id: &ID, entity: &T) -> Result<Option<T>, sqlx::Error>`
+/// - `delete_by_id(&self, id: &ID) -> Result<u64, sqlx::Error>` (rows deleted)
+///
+/// It also generates a `<YourStruct>Patch` struct with every non-key field as `Option<_>` and
+/// implements `PartialUpdateOps<ID, T>`:
+/// - `update_partial(&self, id: &ID, patch: &TPatch) -> Result<u64, sqlx::Error>`
+///
+/// `UpsertOps<T>`, and `SoftDeleteOps<ID, T>` when `soft_delete` is set
+/// (`restore_by_id`, `hard_delete_by_id`, `get_by_id_with_deleted`,
+/// `execute_select_as_only_not_deleted`). The upsert methods are:
+/// - `upsert(&self, entity: &T) -> Result<(), sqlx::Error>`
+/// - `upsert_batch(&self, entities: &[T]) -> Result<(), sqlx::Error>`
+/// - `insert_ignore(&self, entity: &T) -> Result<bool, sqlx::Error>`
+///
+/// Upserts conflict on `#[crud(conflict = "...")]`, else the `#[crud(unique)]` field, else the
+/// primary key. Several unique fields are separate constraints and need an explicit `conflict`. In that last case a `generated` key is written by the upserts like any other
+/// column, so an entity carrying an existing key updates that row; PostgreSQL rejects a `NULL` key.
+///
+/// and `SchemaVerify<T>`, which compares the live table with the struct:
+/// - `verify_schema(&self) -> Result<SchemaReport, sqlx::Error>`
+///
+/// ### SQL Constants
+/// The statements are built at compile time and exposed as associated consts, which the
+/// generated methods execute:
+/// - `T::SQL_INSERT` (with `RETURNING <key>` for a generated key on PostgreSQL/SQLite)
+/// - `T::SQL_SELECT_BY_ID`
+/// - `T::SQL_UPDATE_BY_ID` (absent when every column is part of the key)
+/// - `T::SQL_DELETE_BY_ID` (the soft-delete `UPDATE` when `soft_delete` is set)
+/// - `T::SQL_UPSERT` and `T::SQL_INSERT_IGNORE` (`INSERT IGNORE` on MySQL)
+///
+/// They use the table as declared; with `SqlDB::with_schema` the table comes from
+/// `TableDialect::table_sql` at runtime.
+/// With several backends each const is suffixed, e.g. `T::SQL_INSERT_POSTGRES`.
+///
+/// Statements that depend on their input are built by associated functions instead:
+/// `T::insert_values_sql(rows)`, the `VALUES` list of a batch insert chunk of at most
+/// `T::INSERT_BATCH_ROWS` rows, and `T::update_partial_sql(&patch)`. Their suffix is lowercase,
+/// e.g. `T::update_partial_sql_postgres`.
+///
+/// ```rust,ignore
+/// assert_eq!(
+///     User::SQL_SELECT_BY_ID,
+///     r#"SELECT * FROM "users" WHERE "id" = $1"#
+/// );
+/// ```
+///
+/// ### Table Schema
+/// The struct itself implements `TableSchema`, describing each stored field's column name,
+/// Rust type, nullability (`Option<T>`) and primary key flag:
+///
+/// ```rust,ignore
+/// use typed_sqlx_client::TableSchema;
+///
+/// for column in User::columns() {
+///     println!("{} -> {} ({})", column.field, column.column, column.rust_type);
+/// }
+/// ```
+///
+/// ### Typed Columns
+/// `T::col` is a generated `<T>Columns` struct with a `TypedColumn<T, FieldType>` per stored
+/// field, for the column-based query helpers of `SqlTable` (`find_by`, `find_one_by`,
+/// `select_column`, `column_sql`) and the conditions of its `find()` builder. These quote
+/// names and number placeholders through `TableDialect<P>`, which the struct implements for
+/// each backend with the rules of the generated statements:
+///
+/// ```rust,ignore
+/// assert_eq!(User::col.email.name(), "email_address"); // #[crud(rename = "email_address")]
+/// let user = users.find_one_by(User::col.email, email).await?;
+/// let recent = users.find().filter(User::col.age.ge(18)).limit(10).fetch_all().await?;
+/// ```
 ///
 /// ## 📚 Usage Examples
 ///
@@ -156,11 +272,113 @@ use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, Meta};
 /// 2. **Automatic detection**: If no field is marked, the first field is used
 /// 3. **Option unwrapping**: `Option<T>` fields are automatically unwrapped to `T` for key operations
 /// 4. **Type safety**: The key type is properly extracted for all CRUD operations
+/// 5. **Composite keys**: Marking several fields yields a tuple key type, in field order;
+///    `get_by_id`, `update_by_id` and `delete_by_id` then match on every key column
 ///
 /// ```rust
 /// // These are equivalent for key operations:
 /// struct User1 { id: i64, name: String }      // Key type: i64
 /// struct User2 { id: Option<i64>, name: String }  // Key type: i64 (unwrapped)
+///
+/// // Composite key: implements CrudOpsRef<(i64, String), Membership>
+/// #[derive(FromRow, CrudOpsRef)]
+/// #[crud(table = "memberships", db = "postgres")]
+/// struct Membership {
+///     #[crud(primary_key)]
+///     user_id: i64,
+///     #[crud(primary_key)]
+///     group_name: String,
+///     role: String,
+/// }
+/// ```
+///
+/// ### Database-Generated Keys
+/// With `#[crud(primary_key, generated)]` the key column is omitted from INSERT statements and
+/// the value assigned by the database is returned. PostgreSQL and SQLite use `RETURNING`;
+/// MySQL uses `last_insert_id()`, so the key type must be convertible from `u64`. InnoDB does
+/// not promise consecutive keys to a multi-row INSERT under its default lock mode, so on MySQL
+/// `insert_batch` inserts rows with a generated key one statement at a time (in one transaction).
+///
+/// ```rust
+/// #[derive(FromRow, CrudOpsRef)]
+/// #[crud(table = "orders", db = "postgres")]
+/// struct Order {
+///     #[crud(primary_key, generated)]
+///     id: Option<i64>,   // BIGSERIAL / GENERATED ALWAYS AS IDENTITY
+///     total: f64,
+/// }
+///
+/// let id: i64 = order_table.insert(&Order { id: None, total: 9.5 }).await?;
+/// let ids: Vec<i64> = order_table.insert_batch(&orders).await?;
+/// ```
+///
+/// ### Optimistic Concurrency
+/// A `#[crud(version)]` field turns `update_by_id` into a compare-and-swap: the statement
+/// only matches a row whose version still equals the entity's, and increments it.
+/// When nothing matches, the update fails with a `typed_sqlx_client::VersionConflict`
+/// (carried in `sqlx::Error::Database`). `update_partial` and an upsert that hits an existing
+/// row increment the version without checking it, and the column is not part of the
+/// generated `Patch` struct.
+///
+/// ```rust
+/// #[derive(FromRow, CrudOpsRef)]
+/// #[crud(table = "documents", db = "postgres")]
+/// struct Document {
+///     #[crud(primary_key)]
+///     id: i64,
+///     body: String,
+///     #[crud(version)]
+///     version: i64,
+/// }
+///
+/// if let Err(e) = doc_table.update_by_id(&doc.id, &doc).await {
+///     if VersionConflict::from_error(&e).is_some() {
+///         // someone else saved first: reload and retry
+///     }
+/// }
+/// ```
+///
+/// ### Strict Mode
+/// `update_by_id`, `update_partial`, `delete_by_id` and, with `soft_delete`, `restore_by_id`
+/// and `hard_delete_by_id` return the number of affected rows.
+/// With `#[crud(strict)]` on the struct, zero rows is an error instead: a
+/// `typed_sqlx_client::NotFound` carrying the table name and the `Debug` form of the key,
+/// so the key type must implement `Debug`. A versioned update still reports a
+/// `VersionConflict`, since a stale version and a missing row cannot be told apart.
+///
+/// ```rust,ignore
+/// #[derive(FromRow, CrudOpsRef)]
+/// #[crud(table = "accounts", db = "postgres", strict)]
+/// struct Account {
+///     #[crud(primary_key)]
+///     id: i64,
+///     balance: i64,
+/// }
+///
+/// let err = account_table.delete_by_id(&404).await.unwrap_err();
+/// assert_eq!(NotFound::from_error(&err).unwrap().id(), "404");
+/// ```
+///
+/// ### Timestamps
+/// `#[crud(created_at)]` and `#[crud(updated_at)]` columns are filled by the generated
+/// `insert`, `insert_batch`, upserts and updates; the entity's own value is ignored.
+/// The bare form writes `CURRENT_TIMESTAMP` from the database clock. With
+/// `= "path::to::clock"` the named function is called for each row and its result bound,
+/// so it must return a type the column can encode. `created_at` is never written by
+/// `update_by_id`, `update_partial` or an upsert conflict; neither column appears in `Patch`.
+///
+/// ```rust
+/// #[derive(FromRow, CrudOpsRef)]
+/// #[crud(table = "posts", db = "postgres")]
+/// struct Post {
+///     #[crud(primary_key)]
+///     id: i64,
+///     title: String,
+///     #[crud(created_at)]
+///     created_at: Option<chrono::DateTime<chrono::Utc>>,
+///     #[crud(updated_at = "chrono::Utc::now")]
+///     updated_at: chrono::DateTime<chrono::Utc>,
+/// }
 /// ```
 ///
 /// ## ⚠️ Error Handling
@@ -176,6 +394,7 @@ use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, Meta};
 /// 1. **"trait bound not satisfied"**: Ensure your types implement `sqlx::Encode + sqlx::Type`
 /// 2. **"column not found"**: Check `#[crud(rename = "...")]` matches database schema
 /// 3. **"table not found"**: Verify `#[crud(table = "...")]` matches actual table name
+///    (names are quoted, so on PostgreSQL the case must match too)
 /// 4. **Type conversion errors**: Ensure Rust types match database column types
 ///
 /// ### Debugging Tips:
@@ -187,484 +406,1746 @@ use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, Meta};
 #[proc_macro_derive(CrudOpsRef, attributes(crud))]
 pub fn derive_crud_ops_ref(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    let (fields, struct_attrs, field_attrs) = match parse_input(&input, "CrudOpsRef") {
+        Ok(parsed) => parsed,
+        Err(e) => return e.to_compile_error().into(),
+    };
 
-    let struct_name = &input.ident;
-    let struct_name_str = struct_name.to_string();
+    // One set of impls per backend; the `<Entity>Patch` struct is shared. When several
+    // backends are requested, each set only exists if this crate's matching feature is on.
+    let gated = struct_attrs.db.len() > 1;
+    let mut patch_struct = None;
+    let mut impls = Vec::new();
+    for &dialect in &struct_attrs.db {
+        match expand_dialect(&input, fields, &struct_attrs, &field_attrs, dialect, gated) {
+            Ok((patch, tokens)) => {
+                patch_struct.get_or_insert(patch);
+                impls.push(if gated {
+                    dialect.feature_gate(tokens)
+                } else {
+                    tokens
+                });
+            }
+            Err(e) => return e.to_compile_error().into(),
+        }
+    }
 
-    // Parse table name from struct attributes
-    let table_name = parse_table_name(&input.attrs, &struct_name_str);
+    let table_schema = expand_table_schema(&input, fields, &struct_attrs, &field_attrs);
+
+    TokenStream::from(quote! {
+        #patch_struct
+        #table_schema
+        #(#impls)*
+    })
+}
 
-    // Parse fields
+/// Derive macro implementing the `TableDdl` trait: `CREATE TABLE` / `DROP TABLE` for the table
+/// described by the struct's `#[crud(...)]` attributes.
+///
+/// It reads the same attributes as `#[derive(CrudOpsRef)]` and is usually derived next to it.
+/// For every backend in `db = "..."` it generates:
+/// - `T::SQL_CREATE_TABLE` (suffixed with the backend when there are several, like the
+///   other `SQL_*` consts)
+/// - `TableDdl` on `SqlTable<P, DB, T>`: `create_table_sql()`, `create_table_if_not_exists()`
+///   and `drop_table()` (`DROP TABLE IF EXISTS`), plus the `ALTER TABLE` statements used by
+///   `MigrationPlan`: `add_column_sql()`, `alter_column_type_sql()` and `drop_column_sql()`
+///
+/// ## Columns
+/// - Each field is a column named after the field or its `#[crud(rename = "...")]`, except
+///   `#[crud(skip)]` fields, which are not stored in the table
+/// - Fields that are not `Option<T>` are `NOT NULL`
+/// - `#[crud(primary_key)]` fields form the `PRIMARY KEY` (the first field if none is marked)
+/// - `#[crud(primary_key, generated)]` integer keys are `AUTO_INCREMENT` on MySQL,
+///   `GENERATED BY DEFAULT AS IDENTITY` on PostgreSQL and the rowid on SQLite
+/// - `#[crud(unique)]` fields are `UNIQUE`; a `conflict = "..."` target gets a `UNIQUE` constraint
+///
+/// ## Column Types
+/// | Rust type | MySQL | PostgreSQL | SQLite |
+/// |-----------|-------|------------|--------|
+/// | `bool` | `BOOLEAN` | `BOOLEAN` | `BOOLEAN` |
+/// | `i16` / `i32` / `i64` | `SMALLINT` / `INT` / `BIGINT` | `SMALLINT` / `INTEGER` / `BIGINT` | `INTEGER` |
+/// | `u8`..`u64` | `TINYINT UNSIGNED`.. | - | `INTEGER` (not `u64`) |
+/// | `f32` / `f64` | `FLOAT` / `DOUBLE` | `REAL` / `DOUBLE PRECISION` | `REAL` |
+/// | `String` | `TEXT` (`VARCHAR(255)` if keyed) | `TEXT` | `TEXT` |
+/// | `Vec<u8>` | `BLOB` (`VARBINARY(255)` if keyed) | `BYTEA` | `BLOB` |
+/// | `Uuid` | `BINARY(16)` | `UUID` | `BLOB` |
+/// | `NaiveDate` / `NaiveTime` | `DATE` / `TIME` | `DATE` / `TIME` | `DATE` / `TIME` |
+/// | `NaiveDateTime` | `DATETIME` | `TIMESTAMP` | `DATETIME` |
+/// | `DateTime<Tz>` | `DATETIME` | `TIMESTAMPTZ` | `DATETIME` |
+/// | `Decimal` / `BigDecimal` | - | `NUMERIC` | - |
+/// | `Json<T>` / `serde_json::Value` | `JSON` | `JSONB` | `TEXT` |
+///
+/// The `time` crate's `Date`, `Time`, `PrimitiveDateTime` and `OffsetDateTime` map like their
+/// `chrono` counterparts. Other types, and the `-` cells, are a compile error until the field
+/// names its type with `#[crud(sql_type = "...")]`; the string is used as written for every
+/// backend, followed by the `NOT NULL` and key constraints.
+///
+/// ## Example
+/// ```rust,ignore
+/// use typed_sqlx_client::{CrudOpsRef, TableDdl};
+///
+/// #[derive(FromRow, CrudOpsRef, TableDdl)]
+/// #[crud(table = "users", db = "postgres")]
+/// struct User {
+///     #[crud(primary_key, generated)]
+///     id: i64,
+///     #[crud(unique)]
+///     email: String,
+///     #[crud(sql_type = "VARCHAR(42)")]
+///     wallet: SqlAddress,
+///     nickname: Option<String>,
+/// }
+///
+/// // CREATE TABLE "users" (
+/// //     "id" BIGINT NOT NULL GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+/// //     "email" TEXT NOT NULL UNIQUE,
+/// //     "wallet" VARCHAR(42) NOT NULL,
+/// //     "nickname" TEXT
+/// // )
+/// println!("{}", User::SQL_CREATE_TABLE);
+/// users.create_table_if_not_exists().await?;
+/// ```
+#[proc_macro_derive(TableDdl, attributes(crud))]
+pub fn derive_table_ddl(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let (fields, struct_attrs, field_attrs) = match parse_input(&input, "TableDdl") {
+        Ok(parsed) => parsed,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let gated = struct_attrs.db.len() > 1;
+    let mut impls = Vec::new();
+    for &dialect in &struct_attrs.db {
+        match ddl::expand_table_ddl(&input, fields, &struct_attrs, &field_attrs, dialect, gated) {
+            Ok(tokens) => impls.push(if gated {
+                dialect.feature_gate(tokens)
+            } else {
+                tokens
+            }),
+            Err(e) => return e.to_compile_error().into(),
+        }
+    }
+
+    TokenStream::from(quote! {
+        #(#impls)*
+    })
+}
+
+/// Fields of a derive input, with the struct's and every field's `#[crud(...)]` options.
+type ParsedInput<'a> = (
+    &'a Punctuated<syn::Field, Token![,]>,
+    StructAttrs,
+    HashMap<&'a syn::Ident, FieldAttrs>,
+);
+
+/// Check that `input` is a struct with named fields and parse its `#[crud(...)]` options,
+/// reporting all attribute errors at once. `derive` names the derive in error messages.
+fn parse_input<'a>(input: &'a DeriveInput, derive: &str) -> syn::Result<ParsedInput<'a>> {
     let fields = match &input.data {
         Data::Struct(data_struct) => match &data_struct.fields {
-            Fields::Named(fields) => &fields.named,
-            _ => panic!("CrudOpsRef can only be derived for structs with named fields"),
+            Fields::Named(fields) if !fields.named.is_empty() => &fields.named,
+            Fields::Named(fields) => {
+                return Err(syn::Error::new_spanned(
+                    fields,
+                    format!("{} requires at least one field", derive),
+                ));
+            }
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    format!(
+                        "{} can only be derived for structs with named fields",
+                        derive
+                    ),
+                ));
+            }
         },
-        _ => panic!("CrudOpsRef can only be derived for structs"),
+        Data::Enum(data) => {
+            return Err(syn::Error::new_spanned(
+                data.enum_token,
+                format!("{} can only be derived for structs", derive),
+            ));
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                format!("{} can only be derived for structs", derive),
+            ));
+        }
     };
 
-    // Find primary key
-    let mut primary_key_field = None;
-    let mut primary_key_type = None;
+    let mut errors: Option<syn::Error> = None;
+    let struct_attrs = StructAttrs::parse(&input.attrs);
+    if let Err(e) = &struct_attrs {
+        errors = Some(e.clone());
+    }
+    let mut field_attrs = HashMap::new();
+    for field in fields.iter() {
+        match FieldAttrs::parse(field) {
+            Ok(attrs) => {
+                field_attrs.insert(field.ident.as_ref().unwrap(), attrs);
+            }
+            Err(e) => match &mut errors {
+                Some(existing) => existing.combine(e),
+                None => errors = Some(e),
+            },
+        }
+    }
+    // The soft-delete column must be one of the stored fields, by name or rename.
+    if let Ok(StructAttrs {
+        soft_delete: Some(column),
+        soft_delete_span: Some(span),
+        ..
+    }) = &struct_attrs
+    {
+        let matches_field = fields.iter().any(|f| {
+            let ident = f.ident.as_ref().unwrap();
+            field_attrs.get(ident).is_some_and(|attrs| {
+                !attrs.skip && attrs.rename.clone().unwrap_or_else(|| ident.to_string()) == *column
+            })
+        });
+        if !matches_field {
+            let e = syn::Error::new(
+                *span,
+                format!(
+                    "`soft_delete` column `{}` does not match any stored field or renamed column",
+                    column
+                ),
+            );
+            match &mut errors {
+                Some(existing) => existing.combine(e),
+                None => errors = Some(e),
+            }
+        }
+    }
+    if let Some(e) = errors {
+        return Err(e);
+    }
+    Ok((fields, struct_attrs.unwrap(), field_attrs))
+}
 
-    for field in fields {
-        let field_name = field.ident.as_ref().unwrap();
-        let field_type = &field.ty;
+/// Generate the backend-independent `TableSchema` impl of the struct `struct_name`.
+fn expand_table_schema(
+    input: &DeriveInput,
+    fields: &Punctuated<syn::Field, Token![,]>,
+    struct_attrs: &StructAttrs,
+    field_attrs: &HashMap<&syn::Ident, FieldAttrs>,
+) -> TokenStream2 {
+    let struct_name = &input.ident;
+    let vis = &input.vis;
+    let table_name = struct_attrs
+        .table
+        .clone()
+        .unwrap_or_else(|| struct_name.to_string());
+    let schema = match &struct_attrs.schema {
+        Some(schema) => quote!(Some(#schema)),
+        None => quote!(None),
+    };
+    // Same default as the CRUD statements: the first field when no key is marked
+    let has_marked_key = fields
+        .iter()
+        .any(|f| field_attrs[f.ident.as_ref().unwrap()].primary_key);
+    // `#[crud(skip)]` fields have no column
+    let columns = fields.iter().enumerate().filter_map(|(index, f)| {
+        let ident = f.ident.as_ref().unwrap();
+        let attrs = &field_attrs[ident];
+        if attrs.skip {
+            return None;
+        }
+        let field = ident.to_string();
+        let column = attrs.rename.clone().unwrap_or_else(|| field.clone());
+        let rust_type = type_name(&f.ty);
+        let nullable = is_option(&f.ty);
+        let primary_key = attrs.primary_key || (!has_marked_key && index == 0);
+        Some(quote! {
+            typed_sqlx_client::ColumnInfo {
+                field: #field,
+                column: #column,
+                rust_type: #rust_type,
+                nullable: #nullable,
+                primary_key: #primary_key,
+            }
+        })
+    });
 
-        // Check if this field is marked as primary key
-        if has_primary_key_attr(&field.attrs) {
-            primary_key_field = Some(field_name.to_string());
-            primary_key_type = Some(field_type.clone());
-            break;
+    let soft_delete_column = struct_attrs.soft_delete.as_ref().map(|column| {
+        quote! {
+            fn soft_delete_column() -> Option<&'static str> {
+                Some(#column)
+            }
+        }
+    });
+
+    // `T::col.<field>`: a typed handle per stored field, so queries name columns through the
+    // struct and a rename only changes the generated SQL name
+    let columns_name = format_ident!("{}Columns", struct_name);
+    let columns_doc = format!(
+        "Typed columns of [`{0}`], one per stored field, available as `{0}::col`.",
+        struct_name
+    );
+    let stored: Vec<&syn::Field> = fields
+        .iter()
+        .filter(|f| !field_attrs[f.ident.as_ref().unwrap()].skip)
+        .collect();
+    let col_idents: Vec<&syn::Ident> = stored.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let col_types: Vec<&syn::Type> = stored.iter().map(|f| &f.ty).collect();
+    let col_docs: Vec<String> = stored
+        .iter()
+        .map(|f| {
+            let ident = f.ident.as_ref().unwrap();
+            let column = field_attrs[ident]
+                .rename
+                .clone()
+                .unwrap_or_else(|| ident.to_string());
+            format!("Column `{}` of field `{}`.", column, ident)
+        })
+        .collect();
+    let col_values = stored.iter().map(|f| {
+        let ident = f.ident.as_ref().unwrap();
+        let field = ident.to_string();
+        let column = field_attrs[ident]
+            .rename
+            .clone()
+            .unwrap_or_else(|| field.clone());
+        quote!(typed_sqlx_client::TypedColumn::new(#column, #field))
+    });
+
+    quote! {
+        impl typed_sqlx_client::TableSchema for #struct_name {
+            fn table_name() -> &'static str {
+                #table_name
+            }
+
+            fn schema() -> Option<&'static str> {
+                #schema
+            }
+
+            fn columns() -> &'static [typed_sqlx_client::ColumnInfo] {
+                const COLUMNS: &[typed_sqlx_client::ColumnInfo] = &[#(#columns),*];
+                COLUMNS
+            }
+
+            #soft_delete_column
+        }
+
+        #[doc = #columns_doc]
+        #[derive(Debug, Clone, Copy)]
+        #vis struct #columns_name {
+            #(
+                #[doc = #col_docs]
+                pub #col_idents: typed_sqlx_client::TypedColumn<#struct_name, #col_types>,
+            )*
+        }
+
+        impl #struct_name {
+            /// Typed columns of this struct, for the query helpers of `SqlTable`.
+            #[allow(non_upper_case_globals)]
+            pub const col: #columns_name = #columns_name {
+                #( #col_idents: #col_values, )*
+            };
         }
     }
+}
+
+/// Generate the trait impls of `input` for one backend, plus the `<Entity>Patch` struct.
+fn expand_dialect(
+    input: &DeriveInput,
+    fields: &Punctuated<syn::Field, Token![,]>,
+    struct_attrs: &StructAttrs,
+    field_attrs: &HashMap<&syn::Ident, FieldAttrs>,
+    dialect: Dialect,
+    gated: bool,
+) -> syn::Result<(TokenStream2, TokenStream2)> {
+    let struct_name = &input.ident;
+    let table_name = struct_attrs
+        .table
+        .clone()
+        .unwrap_or_else(|| struct_name.to_string());
+
+    let backend = dialect.backend();
+    let row = dialect.row();
+    let arguments_bound = dialect.arguments_bound();
+    // Identifiers are quoted for the backend; `table_name` stays bare for `table_name()` and errors.
+    // The schema comes from `#[crud(schema = "...")]` unless `SqlDB::with_schema` overrides it,
+    // so the qualified name is built at runtime.
+    let schema = &struct_attrs.schema;
+    let quoted_table = dialect.quote_path(&table_name);
+    let (default_sql_table, default_qualified_table) = match &schema {
+        Some(schema) => (
+            format!("{}.{}", dialect.quote_path(schema), quoted_table),
+            format!("{}.{}", schema, table_name),
+        ),
+        None => (quoted_table.clone(), table_name.clone()),
+    };
+    // A dotted table name such as `billing.invoices` carries its schema, which a runtime
+    // override replaces.
+    let (dotted_schema, bare_table_name) = match table_name.rsplit_once('.') {
+        Some((schema, table)) => (Some(schema.to_string()), table.to_string()),
+        None => (None, table_name.clone()),
+    };
+    let quoted_bare_table = dialect.quote_ident(&bare_table_name);
+    let quote_schema = dialect.quote_ident_expr(quote!(schema));
+    let table_dialect = quote!(<#struct_name as typed_sqlx_client::TableDialect<#backend>>);
+    let sql_table = quote!(#table_dialect::table_sql(self.schema()));
+    // Statements are fixed at expansion time against the default table; with a runtime
+    // schema override they are reassembled around `TableDialect::table_sql`.
+    let schema_table = quote!(#table_dialect::table_sql(Some(schema)));
+    let resolve_sql = |statement: &str, sql: TokenStream2| {
+        resolve_schema(&schema_table, &default_sql_table, statement, sql)
+    };
+    // `SQL_INSERT` etc., suffixed with the backend when the struct derives for several.
+    let suffix = if gated { dialect.const_suffix() } else { "" };
+    let sql_insert = format_ident!("SQL_INSERT{}", suffix);
+    let sql_select_by_id = format_ident!("SQL_SELECT_BY_ID{}", suffix);
+    let sql_update_by_id = format_ident!("SQL_UPDATE_BY_ID{}", suffix);
+    let sql_delete_by_id = format_ident!("SQL_DELETE_BY_ID{}", suffix);
+    let static_sql = |sql: String| resolve_sql(&sql, quote!(#sql));
+
+    let attrs_of = |f: &syn::Field| &field_attrs[f.ident.as_ref().unwrap()];
+    // Quoted column name of a field, honouring `#[crud(rename = "...")]`
+    let column_name = |f: &syn::Field| {
+        let name = attrs_of(f)
+            .rename
+            .clone()
+            .unwrap_or_else(|| f.ident.as_ref().unwrap().to_string());
+        dialect.quote_ident(&name)
+    };
+
+    // Find primary key columns; several marked fields form a composite key
+    let mut pk_fields: Vec<&syn::Field> =
+        fields.iter().filter(|f| attrs_of(f).primary_key).collect();
 
     // Default to first field if no primary key is marked
-    let (primary_key_field, primary_key_type) =
-        if let (Some(field), Some(typ)) = (primary_key_field, primary_key_type) {
-            let pk_ty = extract_option_inner_type_deep(&typ);
-            (field, pk_ty.clone())
-        } else {
-            let first_field = fields
-                .iter()
-                .next()
-                .expect("Struct must have at least one field");
-            let field_name = first_field.ident.as_ref().unwrap().to_string();
-            let field_type = &first_field.ty;
-            let pk_ty = extract_option_inner_type_deep(field_type);
-            (field_name, pk_ty.clone())
-        };
+    if pk_fields.is_empty() {
+        pk_fields.push(&fields[0]);
+    }
+    let is_pk = |f: &syn::Field| pk_fields.iter().any(|pk| pk.ident == f.ident);
+
+    let pk_names: Vec<String> = pk_fields.iter().map(|f| column_name(f)).collect();
+    let pk_types: Vec<_> = pk_fields
+        .iter()
+        .map(|f| extract_option_inner_type_deep(&f.ty))
+        .collect();
+    let primary_key_type = if pk_types.len() == 1 {
+        let pk_ty = pk_types[0];
+        quote!(#pk_ty)
+    } else {
+        quote!((#(#pk_types),*))
+    };
+    // Expressions binding each key component of `id`
+    let id_binds: Vec<_> = if pk_types.len() == 1 {
+        vec![quote!(id)]
+    } else {
+        (0..pk_types.len())
+            .map(|i| {
+                let index = syn::Index::from(i);
+                quote!(&id.#index)
+            })
+            .collect()
+    };
+
+    // `#[crud(skip)]`, `insertable = false` and `updatable = false` keep columns out of the
+    // generated writes; such fields are still read through `FromRow`.
+    let is_skipped = |f: &syn::Field| attrs_of(f).skip;
+    let writes_on_insert = |f: &syn::Field| attrs_of(f).writes_on_insert();
+    let writes_on_update = |f: &syn::Field| attrs_of(f).writes_on_update();
 
     // Generate field idents, field names, and placeholders
-    let field_idents: Vec<_> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
-    let field_names: Vec<String> = fields
+    let field_types: Vec<_> = fields
         .iter()
-        .map(|f| get_crud_rename(&f.attrs).unwrap_or_else(|| f.ident.as_ref().unwrap().to_string()))
+        .filter(|f| !is_skipped(f))
+        .map(|f| &f.ty)
         .collect();
-    let field_types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
-    let non_pk_idents: Vec<_> = fields
+    // A database-generated key is left out of INSERT statements and returned instead
+    let generated_key = pk_fields.iter().any(|f| attrs_of(f).generated);
+    if generated_key && pk_fields.len() > 1 {
+        let field = pk_fields.iter().find(|f| attrs_of(f).generated).unwrap();
+        return Err(syn::Error::new_spanned(
+            field.ident.as_ref().unwrap(),
+            "`generated` is only supported on a single-column primary key",
+        ));
+    }
+    let is_insertable = |f: &syn::Field| !(generated_key && is_pk(f)) && writes_on_insert(f);
+
+    // `#[crud(created_at)]` / `#[crud(updated_at)]` columns are written from a clock
+    // rather than from the entity.
+    let created_at = |f: &syn::Field| attrs_of(f).created_at.as_ref();
+    let updated_at = |f: &syn::Field| attrs_of(f).updated_at.as_ref();
+    let column_value = |f: &syn::Field| {
+        let ident = f.ident.as_ref().unwrap();
+        match created_at(f).or_else(|| updated_at(f)) {
+            Some(source) => source.value(),
+            None => ColumnValue::Bind(quote!(&entity.#ident)),
+        }
+    };
+    let insert_values: Vec<ColumnValue> = fields
         .iter()
-        .filter(|f| f.ident.as_ref().unwrap().to_string() != primary_key_field)
-        .map(|f| f.ident.as_ref().unwrap())
+        .filter(|f| is_insertable(f))
+        .map(column_value)
         .collect();
-    let non_pk_names: Vec<String> = fields
+    let insert_binds: Vec<_> = insert_values.iter().filter_map(ColumnValue::bind).collect();
+    let insert_names: Vec<String> = fields
         .iter()
-        .filter(|f| f.ident.as_ref().unwrap().to_string() != primary_key_field)
-        .map(|f| get_crud_rename(&f.attrs).unwrap_or_else(|| f.ident.as_ref().unwrap().to_string()))
+        .filter(|f| is_insertable(f))
+        .map(column_name)
         .collect();
+    if insert_names.is_empty() {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            "CrudOpsRef requires at least one column that is written on insert",
+        ));
+    }
+
+    let insert_placeholders = dialect.row_values(&insert_values);
+    let insert_fields = insert_names.join(", ");
+    let insert_sql = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        default_sql_table, insert_fields, insert_placeholders
+    );
+    let id_where = key_condition(dialect, &pk_names, 1);
+    let select_by_key_sql = static_sql(format!(
+        "SELECT * FROM {} WHERE {}",
+        default_sql_table, id_where
+    ));
 
-    let db_type = parse_db_type(&input.attrs);
+    // A `#[crud(version)]` column is bumped by every update and must still hold the value
+    // the entity was read with, otherwise the update is rejected as a conflict.
+    let version_fields: Vec<_> = fields.iter().filter(|f| attrs_of(f).version).collect();
+    if let Some(extra) = version_fields.get(1) {
+        return Err(syn::Error::new_spanned(
+            extra.ident.as_ref().unwrap(),
+            "only one field can be marked `#[crud(version)]`",
+        ));
+    }
+    let version_field = version_fields.first().copied();
+    if let Some(field) = version_field.filter(|f| is_pk(f)) {
+        return Err(syn::Error::new_spanned(
+            field.ident.as_ref().unwrap(),
+            "a primary key field cannot also be the `#[crud(version)]` column",
+        ));
+    }
+    // With `#[crud(soft_delete = "col")]`, deleting stamps the column and reads skip stamped rows.
+    let soft_delete = struct_attrs
+        .soft_delete
+        .as_ref()
+        .map(|column| dialect.quote_ident(column));
+    // `created_at` is only ever written by inserts, the soft-delete column only by `delete_by_id`.
+    let is_soft_delete = |f: &syn::Field| soft_delete.as_ref() == Some(&column_name(f));
+    let is_updatable = |f: &syn::Field| {
+        !is_pk(f)
+            && !attrs_of(f).version
+            && created_at(f).is_none()
+            && !is_soft_delete(f)
+            && writes_on_update(f)
+    };
+    let update_names: Vec<String> = fields
+        .iter()
+        .filter(|f| is_updatable(f))
+        .map(column_name)
+        .collect();
+    let update_values: Vec<ColumnValue> = fields
+        .iter()
+        .filter(|f| is_updatable(f))
+        .map(|f| {
+            let ident = f.ident.as_ref().unwrap();
+            match updated_at(f) {
+                Some(source) => source.value(),
+                None => ColumnValue::Bind(quote!(&entity.#ident)),
+            }
+        })
+        .collect();
+    let update_binds: Vec<_> = update_values.iter().filter_map(ColumnValue::bind).collect();
+    let mut next_placeholder = 1;
+    let mut set_parts: Vec<String> = update_names
+        .iter()
+        .zip(&update_values)
+        .map(|(name, value)| format!("{} = {}", name, value.sql(dialect, &mut next_placeholder)))
+        .collect();
+    let mut update_id_where = key_condition(dialect, &pk_names, next_placeholder);
+    let mut version_binds = Vec::new();
+    if let Some(field) = version_field {
+        let name = column_name(field);
+        let ident = field.ident.as_ref().unwrap();
+        set_parts.push(format!("{} = {} + 1", name, name));
+        update_id_where = format!(
+            "{} AND {} = {}",
+            update_id_where,
+            name,
+            dialect.placeholder(next_placeholder + pk_names.len())
+        );
+        version_binds.push(quote!(&entity.#ident));
+    }
+    // Soft-deleted rows are not updated, as they are not read.
+    if let Some(column) = &soft_delete {
+        update_id_where = format!("{} AND {} IS NULL", update_id_where, column);
+    }
+    let set_sql = set_parts.join(", ");
+    let has_update_columns = !set_parts.is_empty();
+    let update_sql = format!(
+        "UPDATE {} SET {} WHERE {}",
+        default_sql_table, set_sql, update_id_where
+    );
+    let update_sql_expr = resolve_sql(&update_sql, quote!(#struct_name::#sql_update_by_id));
+    // Zero matched rows on a versioned update means the expected version is stale.
+    let version_check = if version_field.is_some() {
+        quote! {
+            if result.rows_affected() == 0 {
+                return Err(typed_sqlx_client::VersionConflict::new(#table_name).into());
+            }
+        }
+    } else {
+        quote!(let _ = result;)
+    };
+    // With `#[crud(strict)]`, a keyed write that matches no row is reported as `NotFound`.
+    let not_found_check = if struct_attrs.strict {
+        quote! {
+            if result.rows_affected() == 0 {
+                return Err(typed_sqlx_client::NotFound::new(#table_name, id).into());
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    let update_body = if !has_update_columns {
+        quote! {
+            async move {
+                let _ = (id, entity);
+                Err(sqlx::Error::InvalidArgument(format!(
+                    "table {} has no non-key columns to update",
+                    #table_name
+                )))
+            }
+        }
+    } else {
+        quote! {
+            async move {
+                let sql = #update_sql_expr;
+                let mut query = sqlx::query(&sql);
+                #(
+                    query = query.bind(#update_binds);
+                )*
+                #(
+                    query = query.bind(#id_binds);
+                )*
+                #(
+                    query = query.bind(#version_binds);
+                )*
+                let mut conn = self.connection().await?;
+                let result = query.execute(&mut *conn).await?;
+                #version_check
+                #not_found_check
+                Ok(result.rows_affected())
+            }
+        }
+    };
+
+    // Multi-row inserts are split so that no statement exceeds the backend bind limit.
+    let rows_per_statement = dialect.rows_per_statement(insert_binds.len());
+    let insert_batch_rows = format_ident!("INSERT_BATCH_ROWS{}", suffix);
+    let insert_values_sql = format_ident!("insert_values_sql{}", suffix.to_lowercase());
+    let insert_values_list = dialect.values_list(quote!(rows), &insert_values);
+    let batch_values = quote!(#struct_name::#insert_values_sql(chunk.len()));
+
+    // `insert` and `insert_batch` either return nothing or the generated keys.
+    let insert_const_sql = if generated_key && dialect.supports_returning() {
+        format!("{} RETURNING {}", insert_sql, pk_names[0])
+    } else {
+        insert_sql.clone()
+    };
+    let insert_sql_expr = resolve_sql(&insert_const_sql, quote!(#struct_name::#sql_insert));
+    let (insert_output, batch_insert_output, insert_body, insert_batch_body) = if !generated_key {
+        let insert_body = quote! {
+            async move {
+                let sql = #insert_sql_expr;
+                let mut query = sqlx::query(&sql);
+                #(
+                    query = query.bind(#insert_binds);
+                )*
+                let mut conn = self.connection().await?;
+                query.execute(&mut *conn).await?;
+                Ok(())
+            }
+        };
+        let insert_batch_body = quote! {
+            async move {
+                let fields = #insert_fields;
+                let mut conn = self.connection().await?;
+                let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+                for chunk in entities.chunks(#struct_name::#insert_batch_rows) {
+                    let values = #batch_values;
+                    let sql = format!(
+                        "INSERT INTO {} ({}) VALUES {}",
+                        #sql_table,
+                        fields,
+                        values
+                    );
+                    let mut query = sqlx::query(&sql);
+                    for entity in chunk {
+                        #(
+                            query = query.bind(#insert_binds);
+                        )*
+                    }
+                    query.execute(&mut *tx).await?;
+                }
+                tx.commit().await?;
+                Ok(())
+            }
+        };
+        (quote!(()), quote!(()), insert_body, insert_batch_body)
+    } else if dialect.supports_returning() {
+        let pk_ty = pk_types[0];
+        let pk_name = &pk_names[0];
+        let insert_body = quote! {
+            async move {
+                let sql = #insert_sql_expr;
+                let mut query = sqlx::query_scalar::<#backend, #pk_ty>(&sql);
+                #(
+                    query = query.bind(#insert_binds);
+                )*
+                let mut conn = self.connection().await?;
+                let id = query.fetch_one(&mut *conn).await?;
+                Ok(id)
+            }
+        };
+        let insert_batch_body = quote! {
+            async move {
+                let fields = #insert_fields;
+                let mut ids = Vec::with_capacity(entities.len());
+                let mut conn = self.connection().await?;
+                let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+                for chunk in entities.chunks(#struct_name::#insert_batch_rows) {
+                    let values = #batch_values;
+                    let sql = format!(
+                        "INSERT INTO {} ({}) VALUES {} RETURNING {}",
+                        #sql_table,
+                        fields,
+                        values,
+                        #pk_name
+                    );
+                    let mut query = sqlx::query_scalar::<#backend, #pk_ty>(&sql);
+                    for entity in chunk {
+                        #(
+                            query = query.bind(#insert_binds);
+                        )*
+                    }
+                    ids.extend(query.fetch_all(&mut *tx).await?);
+                }
+                tx.commit().await?;
+                Ok(ids)
+            }
+        };
+        (
+            quote!(#pk_ty),
+            quote!(Vec<#pk_ty>),
+            insert_body,
+            insert_batch_body,
+        )
+    } else {
+        // MySQL has no RETURNING; generated keys come from `last_insert_id()`.
+        let pk_ty = pk_types[0];
+        let to_key = |value: proc_macro2::TokenStream| {
+            quote! {
+                <#pk_ty as std::convert::TryFrom<u64>>::try_from(#value)
+                    .map_err(|e| sqlx::Error::Decode(Box::new(e)))?
+            }
+        };
+        let insert_id = to_key(quote!(result.last_insert_id()));
+        let insert_body = quote! {
+            async move {
+                let sql = #insert_sql_expr;
+                let mut query = sqlx::query(&sql);
+                #(
+                    query = query.bind(#insert_binds);
+                )*
+                let mut conn = self.connection().await?;
+                let result = query.execute(&mut *conn).await?;
+                let id = #insert_id;
+                Ok(id)
+            }
+        };
+        // A multi-row INSERT only gets consecutive keys under `innodb_autoinc_lock_mode` 0 or 1,
+        // and the default since MySQL 8.0 is 2, so each row is inserted on its own to read its
+        // key from `last_insert_id()`.
+        let insert_batch_body = quote! {
+            async move {
+                let sql = #insert_sql_expr;
+                let mut ids = Vec::with_capacity(entities.len());
+                let mut conn = self.connection().await?;
+                let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+                for entity in entities {
+                    let mut query = sqlx::query(&sql);
+                    #(
+                        query = query.bind(#insert_binds);
+                    )*
+                    let result = query.execute(&mut *tx).await?;
+                    ids.push(#insert_id);
+                }
+                tx.commit().await?;
+                Ok(ids)
+            }
+        };
+        (
+            quote!(#pk_ty),
+            quote!(Vec<#pk_ty>),
+            insert_body,
+            insert_batch_body,
+        )
+    };
+
+    // `insert_returning` / `update_by_id_returning` read the row back as stored.
+    let pk_idents: Vec<_> = pk_fields
+        .iter()
+        .map(|f| f.ident.as_ref().unwrap())
+        .collect();
+    let returning_version_check = if version_field.is_some() {
+        quote! {
+            if stored.is_none() {
+                return Err(typed_sqlx_client::VersionConflict::new(#table_name).into());
+            }
+        }
+    } else {
+        quote!()
+    };
+    let (insert_returning_body, update_returning_body) = if dialect.supports_returning() {
+        let insert_returning_sql = static_sql(format!("{} RETURNING *", insert_sql));
+        let update_returning_sql = static_sql(format!("{} RETURNING *", update_sql));
+        let insert_returning_body = quote! {
+            async move {
+                let sql = #insert_returning_sql;
+                let mut query = sqlx::query_as::<#backend, #struct_name>(&sql);
+                #(
+                    query = query.bind(#insert_binds);
+                )*
+                let mut conn = self.connection().await?;
+                let stored = query.fetch_one(&mut *conn).await?;
+                Ok(stored)
+            }
+        };
+        let update_returning_body = quote! {
+            async move {
+                let sql = #update_returning_sql;
+                let mut query = sqlx::query_as::<#backend, #struct_name>(&sql);
+                #(
+                    query = query.bind(#update_binds);
+                )*
+                #(
+                    query = query.bind(#id_binds);
+                )*
+                #(
+                    query = query.bind(#version_binds);
+                )*
+                let mut conn = self.connection().await?;
+                let stored = query.fetch_optional(&mut *conn).await?;
+                #returning_version_check
+                Ok(stored)
+            }
+        };
+        (insert_returning_body, update_returning_body)
+    } else {
+        // Without RETURNING the row is read back by key inside the same transaction.
+        let (execute_insert, inserted_key_binds) = if generated_key {
+            (
+                quote!(let last_insert_id = query.execute(&mut *tx).await?.last_insert_id();),
+                vec![quote!(last_insert_id)],
+            )
+        } else {
+            (
+                quote!(query.execute(&mut *tx).await?;),
+                pk_idents
+                    .iter()
+                    .map(|ident| quote!(&entity.#ident))
+                    .collect(),
+            )
+        };
+        let insert_returning_body = quote! {
+            async move {
+                let sql = #insert_sql_expr;
+                let mut conn = self.connection().await?;
+                let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+                let mut query = sqlx::query(&sql);
+                #(
+                    query = query.bind(#insert_binds);
+                )*
+                #execute_insert
+                let select_sql = #select_by_key_sql;
+                let stored = sqlx::query_as::<#backend, #struct_name>(&select_sql)
+                    #(.bind(#inserted_key_binds))*
+                    .fetch_one(&mut *tx)
+                    .await?;
+                tx.commit().await?;
+                Ok(stored)
+            }
+        };
+        let update_returning_body = quote! {
+            async move {
+                let sql = #update_sql_expr;
+                let mut conn = self.connection().await?;
+                let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+                let mut query = sqlx::query(&sql);
+                #(
+                    query = query.bind(#update_binds);
+                )*
+                #(
+                    query = query.bind(#id_binds);
+                )*
+                #(
+                    query = query.bind(#version_binds);
+                )*
+                let result = query.execute(&mut *tx).await?;
+                #version_check
+                let select_sql = #select_by_key_sql;
+                let stored = sqlx::query_as::<#backend, #struct_name>(&select_sql)
+                    #(.bind(#id_binds))*
+                    .fetch_optional(&mut *tx)
+                    .await?;
+                tx.commit().await?;
+                Ok(stored)
+            }
+        };
+        (insert_returning_body, update_returning_body)
+    };
+    let update_returning_body = if !has_update_columns {
+        quote! {
+            async move {
+                let _ = (id, entity);
+                Err(sqlx::Error::InvalidArgument(format!(
+                    "table {} has no non-key columns to update",
+                    #table_name
+                )))
+            }
+        }
+    } else {
+        update_returning_body
+    };
 
-    let expanded = match db_type.as_str() {
-        "postgres" => {
-            let pg_placeholders: Vec<String> =
-                (1..=field_names.len()).map(|i| format!("${}", i)).collect();
-            let pg_placeholders_tokens: Vec<_> = pg_placeholders
+    // Upserts resolve conflicts on `#[crud(conflict = "...")]`, then the `#[crud(unique)]` field,
+    // then the primary key. Each unique field is its own UNIQUE constraint, so several of them
+    // are no valid target together.
+    let unique_fields: Vec<_> = fields.iter().filter(|f| attrs_of(f).unique).collect();
+    let conflict_names: Vec<String> = match &struct_attrs.conflict {
+        Some(columns) => columns.iter().map(|c| dialect.quote_ident(c)).collect(),
+        None => match unique_fields.as_slice() {
+            [] => pk_names.clone(),
+            [field] => vec![column_name(field)],
+            [_, extra, ..] => {
+                return Err(syn::Error::new_spanned(
+                    extra,
+                    "several `#[crud(unique)]` fields need a `#[crud(conflict = \"...\")]` upsert target",
+                ))
+            }
+        },
+    };
+    let upsert_update_names: Vec<String> = fields
+        .iter()
+        .filter(|f| is_insertable(f) && writes_on_update(f) && created_at(f).is_none())
+        .filter(|f| !attrs_of(f).version)
+        .map(column_name)
+        .filter(|name| !conflict_names.contains(name))
+        .collect();
+    // Overwriting the version from the entity would let a stale upsert reset it, so the
+    // conflicting row's version is bumped instead, as by `update_partial`.
+    let upsert_version_bump = version_field.map(|f| format!("{0} = {0} + 1", column_name(f)));
+    // A generated key left out of the INSERT could never conflict, so upserts falling back
+    // to the primary key write the entity's key like any other column.
+    let keyed_upsert = generated_key
+        && struct_attrs.conflict.is_none()
+        && !fields.iter().any(|f| attrs_of(f).unique);
+    let is_upserted = |f: &syn::Field| is_insertable(f) || (keyed_upsert && is_pk(f));
+    let upsert_values: Vec<ColumnValue> = fields
+        .iter()
+        .filter(|f| is_upserted(f))
+        .map(column_value)
+        .collect();
+    let upsert_binds: Vec<_> = upsert_values.iter().filter_map(ColumnValue::bind).collect();
+    let upsert_fields = fields
+        .iter()
+        .filter(|f| is_upserted(f))
+        .map(column_name)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let upsert_insert_sql = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        default_sql_table,
+        upsert_fields,
+        dialect.row_values(&upsert_values)
+    );
+    let upsert_rows = dialect.rows_per_statement(upsert_binds.len());
+    let upsert_values_list = dialect.values_list(quote!(chunk.len()), &upsert_values);
+    let conflict_sql = conflict_names.join(", ");
+    let (upsert_clause, insert_ignore_statement) = match dialect {
+        Dialect::MySql => {
+            // MySQL reports a conflict on any unique key; the target columns are not named.
+            let noop = format!("{0} = {0}", pk_names[0]);
+            let assignments: Vec<String> = upsert_update_names
                 .iter()
-                .map(|s| syn::LitStr::new(s, proc_macro2::Span::call_site()))
+                .map(|name| format!("{0} = VALUES({0})", name))
+                .chain(upsert_version_bump.clone())
                 .collect();
-            let pg_set_exprs: Vec<String> = non_pk_names
+            let updates = if assignments.is_empty() {
+                noop.clone()
+            } else {
+                assignments.join(", ")
+            };
+            // sqlx connects with CLIENT_FOUND_ROWS, so a no-op `ON DUPLICATE KEY UPDATE` still
+            // reports the existing row as affected; `INSERT IGNORE` reports 0 on a conflict.
+            (
+                format!("ON DUPLICATE KEY UPDATE {}", updates),
+                upsert_insert_sql.replacen("INSERT INTO", "INSERT IGNORE INTO", 1),
+            )
+        }
+        Dialect::Postgres | Dialect::Sqlite => {
+            let assignments: Vec<String> = upsert_update_names
+                .iter()
+                .map(|name| format!("{0} = EXCLUDED.{0}", name))
+                .chain(upsert_version_bump.clone())
+                .collect();
+            let action = if assignments.is_empty() {
+                "DO NOTHING".to_string()
+            } else {
+                format!("DO UPDATE SET {}", assignments.join(", "))
+            };
+            (
+                format!("ON CONFLICT ({}) {}", conflict_sql, action),
+                format!(
+                    "{} ON CONFLICT ({}) DO NOTHING",
+                    upsert_insert_sql, conflict_sql
+                ),
+            )
+        }
+    };
+
+    let upsert_statement = format!("{} {}", upsert_insert_sql, upsert_clause);
+    let sql_upsert = format_ident!("SQL_UPSERT{}", suffix);
+    let sql_insert_ignore = format_ident!("SQL_INSERT_IGNORE{}", suffix);
+    let upsert_sql = resolve_sql(&upsert_statement, quote!(#struct_name::#sql_upsert));
+    let insert_ignore_sql = resolve_sql(
+        &insert_ignore_statement,
+        quote!(#struct_name::#sql_insert_ignore),
+    );
+    let single_insert_sql = static_sql(insert_sql.clone());
+
+    // `<Entity>Patch` carries every column `update_by_id` writes from the entity, as `Option<T>`;
+    // only `Some` fields are written.
+    let vis = &input.vis;
+    let patch_name = format_ident!("{}Patch", struct_name);
+    let patch_doc = format!(
+        "Partial update for [`{}`]: only fields set to `Some` are written by `update_partial`.",
+        struct_name
+    );
+    let is_patchable = |f: &syn::Field| is_updatable(f) && updated_at(f).is_none();
+    let update_partial_sql = format_ident!("update_partial_sql{}", suffix.to_lowercase());
+    let patch_sql = resolve_sql(
+        &format!("UPDATE {} SET", default_sql_table),
+        quote!(statement.as_str()),
+    );
+    let patch_idents: Vec<_> = fields
+        .iter()
+        .filter(|f| is_patchable(f))
+        .map(|f| f.ident.as_ref().unwrap())
+        .collect();
+    let patch_types: Vec<_> = fields
+        .iter()
+        .filter(|f| is_patchable(f))
+        .map(|f| &f.ty)
+        .collect();
+    let patch_assignments: Vec<_> = fields
+        .iter()
+        .filter(|f| is_patchable(f))
+        .map(column_name)
+        .map(|name| match dialect {
+            Dialect::Postgres => quote! {
+                assignments.push(format!("{} = ${}", #name, next));
+                next += 1;
+            },
+            Dialect::MySql | Dialect::Sqlite => {
+                let assignment = format!("{} = ?", name);
+                quote!(assignments.push(String::from(#assignment));)
+            }
+        })
+        .collect();
+    // Columns maintained by the macro are written by every non-empty patch.
+    let mut patch_maintained = Vec::new();
+    let mut patch_maintained_binds = Vec::new();
+    for field in fields.iter() {
+        let name = column_name(field);
+        if let Some(source) = updated_at(field) {
+            match source.value() {
+                ColumnValue::Sql(sql) => {
+                    let assignment = format!("{} = {}", name, sql);
+                    patch_maintained.push(quote!(assignments.push(String::from(#assignment));));
+                }
+                ColumnValue::Bind(bind) => {
+                    patch_maintained.push(match dialect {
+                        Dialect::Postgres => quote! {
+                            assignments.push(format!("{} = ${}", #name, next));
+                            next += 1;
+                        },
+                        Dialect::MySql | Dialect::Sqlite => {
+                            let assignment = format!("{} = ?", name);
+                            quote!(assignments.push(String::from(#assignment));)
+                        }
+                    });
+                    patch_maintained_binds.push(bind);
+                }
+            }
+        } else if version_field.is_some_and(|f| f.ident == field.ident) {
+            let assignment = format!("{} = {} + 1", name, name);
+            patch_maintained.push(quote!(assignments.push(String::from(#assignment));));
+        }
+    }
+    let (patch_counter, patch_key_where) = match dialect {
+        Dialect::Postgres => {
+            let mut conditions: Vec<_> = pk_names
                 .iter()
                 .enumerate()
-                .map(|(i, name)| format!("{} = ${}", name, i + 1))
+                .map(|(i, name)| quote!(format!("{} = ${}", #name, next + #i)))
                 .collect();
-            let pg_set_sql = pg_set_exprs.join(", ");
-            let update_pk_index = non_pk_names.len() + 1;
+            if let Some(column) = &soft_delete {
+                let guard = format!("{} IS NULL", column);
+                conditions.push(quote!(String::from(#guard)));
+            }
+            let counter = if patch_idents.is_empty() && patch_maintained_binds.is_empty() {
+                quote!(let next = 1usize;)
+            } else {
+                quote!(let mut next = 1usize;)
+            };
+            (counter, quote!([#(#conditions),*].join(" AND ")))
+        }
+        Dialect::MySql | Dialect::Sqlite => {
+            let mut key_where = key_condition(dialect, &pk_names, 1);
+            if let Some(column) = &soft_delete {
+                key_where = format!("{} AND {} IS NULL", key_where, column);
+            }
+            (quote!(), quote!(String::from(#key_where)))
+        }
+    };
 
-            quote! {
-                impl<DB> typed_sqlx_client::CrudOpsRef<#primary_key_type, #struct_name>
-                    for typed_sqlx_client::SqlTable<sqlx::Postgres, DB, #struct_name>
-                where
-                    DB: Send + Sync,
-                    #struct_name: for<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> + Send + Sync,
-                    for<'a> &'a str: sqlx::ColumnIndex<sqlx::postgres::PgRow>,
-                    sqlx::postgres::PgArguments: for<'q> sqlx::IntoArguments<'q, sqlx::Postgres>,
-                    for<'c> &'c sqlx::Pool<sqlx::Postgres>: sqlx::Executor<'c, Database = sqlx::Postgres>,
-                    #(
-                        #field_types: for<'r> sqlx::Encode<'r, sqlx::Postgres> + sqlx::Type<sqlx::Postgres>,
-                    )*
-                {
-                    type Error = sqlx::Error;
+    let where_clause = quote! {
+        where
+            DB: Send + Sync,
+            #struct_name: for<'r> sqlx::FromRow<'r, #row> + Send + Sync,
+            for<'a> &'a str: sqlx::ColumnIndex<#row>,
+            #arguments_bound,
+            for<'c> &'c mut <#backend as sqlx::Database>::Connection: sqlx::Executor<'c, Database = #backend>,
+            #(
+                #field_types: for<'r> sqlx::Encode<'r, #backend> + sqlx::Type<#backend>,
+            )*
+    };
 
-                    fn table_name(&self) -> &'static str {
-                        #table_name
+    let (delete_sql, visible_id_where) = match &soft_delete {
+        Some(column) => (
+            format!(
+                "UPDATE {} SET {} = CURRENT_TIMESTAMP WHERE {} AND {} IS NULL",
+                default_sql_table, column, id_where, column
+            ),
+            format!("{} AND {} IS NULL", id_where, column),
+        ),
+        None => (
+            format!("DELETE FROM {} WHERE {}", default_sql_table, id_where),
+            id_where.clone(),
+        ),
+    };
+    let select_sql = format!(
+        "SELECT * FROM {} WHERE {}",
+        default_sql_table, visible_id_where
+    );
+    let delete_sql_expr = resolve_sql(&delete_sql, quote!(#struct_name::#sql_delete_by_id));
+    let select_sql_expr = resolve_sql(&select_sql, quote!(#struct_name::#sql_select_by_id));
+    let soft_delete_impl = match &soft_delete {
+        Some(column) => {
+            let column_label = struct_attrs.soft_delete.as_deref().unwrap_or_default();
+            // MySQL rejects a derived table with two columns of the same name.
+            let duplicate_check = match dialect {
+                Dialect::MySql => quote! {
+                    let duplicate = names.iter().enumerate().find_map(|(i, name)| {
+                        names[..i]
+                            .iter()
+                            .any(|other| other.eq_ignore_ascii_case(name))
+                            .then_some(*name)
+                    });
+                    if let Some(name) = duplicate {
+                        return Err(sqlx::Error::InvalidArgument(format!(
+                            "query selects column {} more than once",
+                            name
+                        )));
                     }
+                },
+                Dialect::Postgres | Dialect::Sqlite => quote!(),
+            };
+            let restore_sql = static_sql(format!(
+                "UPDATE {} SET {} = NULL WHERE {}",
+                default_sql_table, column, id_where
+            ));
+            let hard_delete_sql = static_sql(format!(
+                "DELETE FROM {} WHERE {}",
+                default_sql_table, id_where
+            ));
+            quote! {
+            impl<DB> typed_sqlx_client::SoftDeleteOps<#primary_key_type, #struct_name>
+                for typed_sqlx_client::SqlTable<#backend, DB, #struct_name>
+            #where_clause
+            {
+                type Error = sqlx::Error;
+                type Database = #backend;
 
-                    fn delete_by_id(&self, id: &#primary_key_type) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
-                        async move {
-                            let sql = format!("DELETE FROM {} WHERE {} = $1", #table_name, #primary_key_field);
-                            sqlx::query(&sql).bind(id).execute(self.get_pool()).await?;
-                            Ok(())
-                        }
+                fn restore_by_id(&self, id: &#primary_key_type) -> impl std::future::Future<Output = Result<u64, Self::Error>> + Send {
+                    async move {
+                        let sql = #restore_sql;
+                        let mut conn = self.connection().await?;
+                        let result = sqlx::query(&sql)
+                            #(.bind(#id_binds))*
+                            .execute(&mut *conn)
+                            .await?;
+                        #not_found_check
+                        Ok(result.rows_affected())
                     }
+                }
 
-                    fn get_by_id(&self, id: &#primary_key_type) -> impl std::future::Future<Output = Result<Option<#struct_name>, Self::Error>> + Send {
-                        async move {
-                            let sql = format!("SELECT * FROM {} WHERE {} = $1", #table_name, #primary_key_field);
-                            let result = sqlx::query_as::<sqlx::Postgres, #struct_name>(&sql)
-                                .bind(id)
-                                .fetch_optional(self.get_pool())
-                                .await?;
-                            Ok(result)
-                        }
+                fn hard_delete_by_id(&self, id: &#primary_key_type) -> impl std::future::Future<Output = Result<u64, Self::Error>> + Send {
+                    async move {
+                        let sql = #hard_delete_sql;
+                        let mut conn = self.connection().await?;
+                        let result = sqlx::query(&sql)
+                            #(.bind(#id_binds))*
+                            .execute(&mut *conn)
+                            .await?;
+                        #not_found_check
+                        Ok(result.rows_affected())
                     }
+                }
 
-                    fn insert(&self, entity: &#struct_name) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
-                        async move {
-                            let fields = [#(#field_names),*].join(", ");
-                            let placeholders = [#(#pg_placeholders_tokens),*].join(", ");
-                            let sql = format!(
-                                "INSERT INTO {} ({}) VALUES ({})",
-                                #table_name,
-                                fields,
-                                placeholders
-                            );
-                            let mut query = sqlx::query(&sql);
-                            #(
-                                query = query.bind(&entity.#field_idents);
-                            )*
-                            query.execute(self.get_pool()).await?;
-                            Ok(())
-                        }
+                fn get_by_id_with_deleted(&self, id: &#primary_key_type) -> impl std::future::Future<Output = Result<Option<#struct_name>, Self::Error>> + Send {
+                    async move {
+                        let sql = #select_by_key_sql;
+                        let mut conn = self.connection().await?;
+                        let result = sqlx::query_as::<#backend, #struct_name>(&sql)
+                            #(.bind(#id_binds))*
+                            .fetch_optional(&mut *conn)
+                            .await?;
+                        Ok(result)
                     }
+                }
 
-                    fn update_by_id(&self, id: &#primary_key_type, entity: &#struct_name) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
-                        async move {
-                            let sql = format!(
-                                "UPDATE {} SET {} WHERE {} = ${}",
-                                #table_name,
-                                #pg_set_sql,
-                                #primary_key_field,
-                                #update_pk_index
-                            );
-                            let mut query = sqlx::query(&sql);
-                            #(
-                                query = query.bind(&entity.#non_pk_idents);
-                            )*
-                            query = query.bind(id);
-                            query.execute(self.get_pool()).await?;
-                            Ok(())
+                fn execute_select_as_only_not_deleted<T>(&self, query: &str) -> impl std::future::Future<Output = Result<Vec<T>, Self::Error>> + Send
+                where
+                    T: for<'r> sqlx::FromRow<'r, #row> + Send + Unpin + 'static,
+                {
+                    let query = query.trim().trim_end_matches(';').to_string();
+                    async move {
+                        if !query.to_lowercase().starts_with("select") {
+                            return Err(sqlx::Error::InvalidArgument(
+                                "Only SELECT queries are allowed".into(),
+                            ));
                         }
-                    }
-
-                    fn insert_batch(&self, entities: &[#struct_name]) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
-                        async move {
-                            let fields = [#(#field_names),*].join(", ");
-                            let placeholders = [#(#pg_placeholders_tokens),*].join(", ");
-                            for entity in entities {
-                                let sql = format!(
-                                    "INSERT INTO {} ({}) VALUES ({})",
-                                    #table_name,
-                                    fields,
-                                    placeholders
-                                );
-                                let mut query = sqlx::query(&sql);
-                                #(
-                                    query = query.bind(&entity.#field_idents);
-                                )*
-                                query.execute(self.get_pool()).await?;
-                            }
-                            Ok(())
+                        let mut conn = self.connection().await?;
+                        // The wrapper filters on the query's own output, so check its columns
+                        // before the database reports an unrelated-looking error.
+                        let described = sqlx::Executor::describe(&mut *conn, &query).await?;
+                        let names: Vec<&str> =
+                            described.columns().iter().map(sqlx::Column::name).collect();
+                        let selected = names
+                            .iter()
+                            .filter(|name| name.eq_ignore_ascii_case(#column_label))
+                            .count();
+                        if selected != 1 {
+                            return Err(sqlx::Error::InvalidArgument(format!(
+                                "query must select the soft-delete column {} exactly once",
+                                #column_label
+                            )));
                         }
+                        #duplicate_check
+                        let sql = format!(
+                            "SELECT * FROM ({}) AS soft_delete_scope WHERE soft_delete_scope.{} IS NULL",
+                            query,
+                            #column
+                        );
+                        let values: Vec<T> = sqlx::query_as(&sql).fetch_all(&mut *conn).await?;
+                        Ok(values)
                     }
                 }
             }
+            }
         }
-        "sqlite" => {
-            let placeholders: Vec<_> = (0..field_names.len()).map(|_| "?").collect::<Vec<_>>();
-            let set_exprs: Vec<_> = non_pk_names
-                .iter()
-                .map(|name| format!("{} = ?", name))
-                .collect();
-            let set_sql = set_exprs.join(", ");
-            quote! {
-                impl<DB> typed_sqlx_client::CrudOpsRef<#primary_key_type, #struct_name>
-                    for typed_sqlx_client::SqlTable<sqlx::Sqlite, DB, #struct_name>
-                where
-                    DB: Send + Sync,
-                    #struct_name: for<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Sync,
-                    for<'a> &'a str: sqlx::ColumnIndex<sqlx::sqlite::SqliteRow>,
-                    for<'q> sqlx::sqlite::SqliteArguments<'q>: sqlx::IntoArguments<'q, sqlx::Sqlite>,
-                    for<'c> &'c sqlx::Pool<sqlx::Sqlite>: sqlx::Executor<'c, Database = sqlx::Sqlite>,
-                    #(
-                        #field_types: for<'r> sqlx::Encode<'r, sqlx::Sqlite> + sqlx::Type<sqlx::Sqlite>,
-                    )*
-                {
-                    type Error = sqlx::Error;
+        None => quote!(),
+    };
 
-                    fn table_name(&self) -> &'static str {
-                        #table_name
-                    }
+    let patch_struct = quote! {
+        #[doc = #patch_doc]
+        #[derive(Default)]
+        #vis struct #patch_name {
+            #(
+                pub #patch_idents: Option<#patch_types>,
+            )*
+        }
+    };
 
-                    fn delete_by_id(&self, id: &#primary_key_type) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
-                        async move {
-                            let sql = format!("DELETE FROM {} WHERE {} = ?", #table_name, #primary_key_field);
-                            sqlx::query(&sql).bind(id).execute(self.get_pool()).await?;
-                            Ok(())
-                        }
-                    }
+    // The statements behind `insert`, `get_by_id`, `update_by_id` and `delete_by_id`, for
+    // logging, `EXPLAIN` or hand-written queries.
+    let const_doc = |method: &str| format!("{} statement run by `{}`.", dialect.name(), method);
+    let insert_doc = const_doc("insert");
+    let select_doc = const_doc("get_by_id");
+    let update_doc = const_doc("update_by_id");
+    let delete_doc = const_doc("delete_by_id");
+    let upsert_doc = const_doc("upsert");
+    let insert_ignore_doc = const_doc("insert_ignore");
+    let batch_rows_doc = format!(
+        "Rows per {} statement of the batch inserts, so none exceeds the bind parameter limit.",
+        dialect.name()
+    );
+    let values_doc = format!(
+        "The `VALUES` list of a {} multi-row insert of `rows` rows, as sent by the batch inserts.",
+        dialect.name()
+    );
+    let patch_sql_doc = format!(
+        "{} statement run by `update_partial` for `patch`, or `None` if it sets no column.",
+        dialect.name()
+    );
+    let update_const = if has_update_columns {
+        quote! {
+            #[doc = #update_doc]
+            pub const #sql_update_by_id: &'static str = #update_sql;
+        }
+    } else {
+        quote!()
+    };
+    let sql_consts = quote! {
+        impl #struct_name {
+            #[doc = #insert_doc]
+            pub const #sql_insert: &'static str = #insert_const_sql;
+            #[doc = #select_doc]
+            pub const #sql_select_by_id: &'static str = #select_sql;
+            #update_const
+            #[doc = #delete_doc]
+            pub const #sql_delete_by_id: &'static str = #delete_sql;
+            #[doc = #upsert_doc]
+            pub const #sql_upsert: &'static str = #upsert_statement;
+            #[doc = #insert_ignore_doc]
+            pub const #sql_insert_ignore: &'static str = #insert_ignore_statement;
+            #[doc = #batch_rows_doc]
+            pub const #insert_batch_rows: usize = #rows_per_statement;
 
-                    fn get_by_id(&self, id: &#primary_key_type) -> impl std::future::Future<Output = Result<Option<#struct_name>, Self::Error>> + Send {
-                        async move {
-                            let sql = format!("SELECT * FROM {} WHERE {} = ?", #table_name, #primary_key_field);
-                            let result = sqlx::query_as::<sqlx::Sqlite, #struct_name>(&sql)
-                                .bind(id)
-                                .fetch_optional(self.get_pool())
-                                .await?;
-                            Ok(result)
-                        }
-                    }
+            #[doc = #values_doc]
+            pub fn #insert_values_sql(rows: usize) -> String {
+                #insert_values_list
+            }
 
-                    fn insert(&self, entity: &#struct_name) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
-                        async move {
-                            let fields = [#(#field_names),*].join(", ");
-                            let placeholders = [#(#placeholders),*].join(", ");
-                            let sql = format!(
-                                "INSERT INTO {} ({}) VALUES ({})",
-                                #table_name,
-                                fields,
-                                placeholders
-                            );
-                            let mut query = sqlx::query(&sql);
-                            #(
-                                query = query.bind(&entity.#field_idents);
-                            )*
-                            query.execute(self.get_pool()).await?;
-                            Ok(())
-                        }
+            #[doc = #patch_sql_doc]
+            pub fn #update_partial_sql(patch: &#patch_name) -> Option<String> {
+                let mut assignments: Vec<String> = Vec::new();
+                #patch_counter
+                #(
+                    if patch.#patch_idents.is_some() {
+                        #patch_assignments
                     }
+                )*
+                if assignments.is_empty() {
+                    return None;
+                }
+                #(#patch_maintained)*
+                Some(format!(
+                    "UPDATE {} SET {} WHERE {}",
+                    #default_sql_table,
+                    assignments.join(", "),
+                    #patch_key_where
+                ))
+            }
+        }
+    };
 
-                    fn update_by_id(&self, id: &#primary_key_type, entity: &#struct_name) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
-                        async move {
-                            let sql = format!(
-                                "UPDATE {} SET {} WHERE {} = ?",
-                                #table_name,
-                                #set_sql,
-                                #primary_key_field
-                            );
-                            let mut query = sqlx::query(&sql);
-                            #(
-                                query = query.bind(&entity.#non_pk_idents);
-                            )*
-                            query = query.bind(id);
-                            query.execute(self.get_pool()).await?;
-                            Ok(())
-                        }
-                    }
+    // `SchemaVerify`: every stored field, with the decode check of its Rust type
+    let verify_fields: Vec<&syn::Field> = fields.iter().filter(|f| !is_skipped(f)).collect();
+    let verify_count = verify_fields.len();
+    let verify_columns: Vec<String> = verify_fields
+        .iter()
+        .map(|f| {
+            attrs_of(f)
+                .rename
+                .clone()
+                .unwrap_or_else(|| f.ident.as_ref().unwrap().to_string())
+        })
+        .collect();
+    let verify_quoted: Vec<String> = verify_fields.iter().map(|f| column_name(f)).collect();
+    let verify_field_names: Vec<String> = verify_fields
+        .iter()
+        .map(|f| f.ident.as_ref().unwrap().to_string())
+        .collect();
+    let verify_rust_types: Vec<String> = verify_fields.iter().map(|f| type_name(&f.ty)).collect();
+    let columns_query = dialect.columns_query();
+    let declared_schema = match schema.as_ref().or(dotted_schema.as_ref()) {
+        Some(schema) => quote!(Some(#schema)),
+        None => quote!(None),
+    };
 
-                    fn insert_batch(&self, entities: &[#struct_name]) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
-                        async move {
-                            let fields = [#(#field_names),*].join(", ");
-                            let placeholders = [#(#placeholders),*].join(", ");
-                            for entity in entities {
-                                let sql = format!(
-                                    "INSERT INTO {} ({}) VALUES ({})",
-                                    #table_name,
-                                    fields,
-                                    placeholders
-                                );
-                                let mut query = sqlx::query(&sql);
-                                #(
-                                    query = query.bind(&entity.#field_idents);
-                                )*
-                                query.execute(self.get_pool()).await?;
-                            }
-                            Ok(())
-                        }
-                    }
+    // `TableDialect`: the quoting and placeholder rules above, for the typed column queries
+    let quote_name = dialect.quote_ident_expr(quote!(name));
+    let placeholder_body = match dialect {
+        Dialect::Postgres => quote!(format!("${}", index)),
+        Dialect::MySql | Dialect::Sqlite => quote! {
+            let _ = index;
+            String::from("?")
+        },
+    };
+    let unbounded_limit = match dialect.unbounded_limit() {
+        Some(limit) => quote!(Some(#limit)),
+        None => quote!(None),
+    };
+
+    let impls = quote! {
+        #sql_consts
+
+        impl typed_sqlx_client::TableDialect<#backend> for #struct_name {
+            fn table_sql(schema: Option<&str>) -> String {
+                match schema {
+                    Some(schema) => format!("{}.{}", #quote_schema, #quoted_bare_table),
+                    None => String::from(#default_sql_table),
                 }
             }
+
+            fn quote_ident(name: &str) -> String {
+                #quote_name
+            }
+
+            fn placeholder(index: usize) -> String {
+                #placeholder_body
+            }
+
+            fn unbounded_limit() -> Option<&'static str> {
+                #unbounded_limit
+            }
         }
-        _ => {
-            // default to MySQL
-            let placeholders: Vec<_> = (0..field_names.len()).map(|_| "?").collect::<Vec<_>>();
-            let set_exprs: Vec<_> = non_pk_names
-                .iter()
-                .map(|name| format!("{} = ?", name))
-                .collect();
-            let set_sql = set_exprs.join(", ");
-            quote! {
-                impl<DB> typed_sqlx_client::CrudOpsRef<#primary_key_type, #struct_name>
-                    for typed_sqlx_client::SqlTable<sqlx::MySql, DB, #struct_name>
-                where
-                    DB: Send + Sync,
-                    #struct_name: for<'r> sqlx::FromRow<'r, sqlx::mysql::MySqlRow> + Send + Sync,
-                    for<'a> &'a str: sqlx::ColumnIndex<sqlx::mysql::MySqlRow>,
-                    sqlx::mysql::MySqlArguments: for<'q> sqlx::IntoArguments<'q, sqlx::MySql>,
-                    for<'c> &'c sqlx::Pool<sqlx::MySql>: sqlx::Executor<'c, Database = sqlx::MySql>,
-                    #(
-                        #field_types: for<'r> sqlx::Encode<'r, sqlx::MySql> + sqlx::Type<sqlx::MySql>,
-                    )*
-                {
-                    type Error = sqlx::Error;
 
-                    fn table_name(&self) -> &'static str {
-                        #table_name
-                    }
+        impl<DB> typed_sqlx_client::SchemaVerify<#struct_name>
+            for typed_sqlx_client::SqlTable<#backend, DB, #struct_name>
+        #where_clause
+        {
+            type Error = sqlx::Error;
 
-                    fn delete_by_id(&self, id: &#primary_key_type) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
-                        async move {
-                            let sql = format!("DELETE FROM {} WHERE {} = ?", #table_name, #primary_key_field);
-                            sqlx::query(&sql).bind(id).execute(self.get_pool()).await?;
-                            Ok(())
-                        }
+            fn verify_schema(&self) -> impl std::future::Future<Output = Result<typed_sqlx_client::SchemaReport, Self::Error>> + Send {
+                async move {
+                    let schema: Option<&str> = match self.schema() {
+                        Some(schema) => Some(schema),
+                        None => #declared_schema,
+                    };
+                    let mut conn = self.connection().await?;
+                    let live: Vec<(String, i64, i64)> = sqlx::query_as(#columns_query)
+                        .bind(schema)
+                        .bind(#bare_table_name)
+                        .fetch_all(&mut *conn)
+                        .await?;
+                    let mut report = typed_sqlx_client::SchemaReport::new(match schema {
+                        Some(schema) => format!("{}.{}", schema, #bare_table_name),
+                        None => String::from(#bare_table_name),
+                    });
+                    let expected: [(&str, &str); #verify_count] = [#((#verify_columns, #verify_field_names)),*];
+                    let present = report.check_columns(&live, &expected);
+                    if present.is_empty() {
+                        return Ok(report);
                     }
-
-                    fn get_by_id(&self, id: &#primary_key_type) -> impl std::future::Future<Output = Result<Option<#struct_name>, Self::Error>> + Send {
-                        async move {
-                            let sql = format!("SELECT * FROM {} WHERE {} = ?", #table_name, #primary_key_field);
-                            let result = sqlx::query_as::<sqlx::MySql, #struct_name>(&sql)
-                                .bind(id)
-                                .fetch_optional(self.get_pool())
-                                .await?;
-                            Ok(result)
+                    // Prepare a query over the existing columns to learn their types
+                    let quoted: [&str; #verify_count] = [#(#verify_quoted),*];
+                    let rust_types: [&str; #verify_count] = [#(#verify_rust_types),*];
+                    let compatible: [fn(&<#backend as sqlx::Database>::TypeInfo) -> bool; #verify_count] =
+                        [#(<#field_types as sqlx::Type<#backend>>::compatible),*];
+                    let selected: Vec<&str> = present.iter().map(|&i| quoted[i]).collect();
+                    let sql = format!("SELECT {} FROM {}", selected.join(", "), #sql_table);
+                    let statement = sqlx::Executor::prepare(&mut *conn, sql.as_str()).await?;
+                    for (column, &i) in sqlx::Statement::columns(&statement).iter().zip(&present) {
+                        let type_info = sqlx::Column::type_info(column);
+                        if !compatible[i](type_info) {
+                            report.push(typed_sqlx_client::SchemaIssue::TypeMismatch {
+                                column: expected[i].0.to_string(),
+                                rust_type: rust_types[i].to_string(),
+                                sql_type: sqlx::TypeInfo::name(type_info).to_string(),
+                            });
                         }
                     }
+                    Ok(report)
+                }
+            }
+        }
 
-                    fn insert(&self, entity: &#struct_name) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
-                        async move {
-                            let fields = [#(#field_names),*].join(", ");
-                            let placeholders = [#(#placeholders),*].join(", ");
-                            let sql = format!(
-                                "INSERT INTO {} ({}) VALUES ({})",
-                                #table_name,
-                                fields,
-                                placeholders
-                            );
-                            let mut query = sqlx::query(&sql);
-                            #(
-                                query = query.bind(&entity.#field_idents);
-                            )*
-                            query.execute(self.get_pool()).await?;
-                            Ok(())
+        impl<DB> typed_sqlx_client::PartialUpdateOps<#primary_key_type, #struct_name>
+            for typed_sqlx_client::SqlTable<#backend, DB, #struct_name>
+        #where_clause
+        {
+            type Error = sqlx::Error;
+            type Patch = #patch_name;
+
+            fn update_partial(&self, id: &#primary_key_type, patch: &#patch_name) -> impl std::future::Future<Output = Result<u64, Self::Error>> + Send {
+                async move {
+                    let Some(statement) = #struct_name::#update_partial_sql(patch) else {
+                        return Err(sqlx::Error::InvalidArgument(format!(
+                            "patch for table {} does not set any column",
+                            #table_name
+                        )));
+                    };
+                    let sql = #patch_sql;
+                    let mut query = sqlx::query(&sql);
+                    #(
+                        if let Some(value) = &patch.#patch_idents {
+                            query = query.bind(value);
                         }
-                    }
+                    )*
+                    #(
+                        query = query.bind(#patch_maintained_binds);
+                    )*
+                    #(
+                        query = query.bind(#id_binds);
+                    )*
+                    let mut conn = self.connection().await?;
+                    let result = query.execute(&mut *conn).await?;
+                    #not_found_check
+                    Ok(result.rows_affected())
+                }
+            }
+        }
 
-                    fn update_by_id(&self, id: &#primary_key_type, entity: &#struct_name) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
-                        async move {
-                            let sql = format!(
-                                "UPDATE {} SET {} WHERE {} = ?",
-                                #table_name,
-                                #set_sql,
-                                #primary_key_field
-                            );
-                            let mut query = sqlx::query(&sql);
+        #soft_delete_impl
+
+        impl<DB> typed_sqlx_client::UpsertOps<#struct_name>
+            for typed_sqlx_client::SqlTable<#backend, DB, #struct_name>
+        #where_clause
+        {
+            type Error = sqlx::Error;
+
+            fn upsert(&self, entity: &#struct_name) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
+                async move {
+                    let sql = #upsert_sql;
+                    let mut query = sqlx::query(&sql);
+                    #(
+                        query = query.bind(#upsert_binds);
+                    )*
+                    let mut conn = self.connection().await?;
+                    query.execute(&mut *conn).await?;
+                    Ok(())
+                }
+            }
+
+            fn upsert_batch(&self, entities: &[#struct_name]) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
+                async move {
+                    let fields = #upsert_fields;
+                    let mut conn = self.connection().await?;
+                    let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+                    for chunk in entities.chunks(#upsert_rows) {
+                        let values = #upsert_values_list;
+                        let sql = format!(
+                            "INSERT INTO {} ({}) VALUES {} {}",
+                            #sql_table,
+                            fields,
+                            values,
+                            #upsert_clause
+                        );
+                        let mut query = sqlx::query(&sql);
+                        for entity in chunk {
                             #(
-                                query = query.bind(&entity.#non_pk_idents);
+                                query = query.bind(#upsert_binds);
                             )*
-                            query = query.bind(id);
-                            query.execute(self.get_pool()).await?;
-                            Ok(())
                         }
+                        query.execute(&mut *tx).await?;
                     }
+                    tx.commit().await?;
+                    Ok(())
+                }
+            }
 
-                    fn insert_batch(&self, entities: &[#struct_name]) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
-                        async move {
-                            let fields = [#(#field_names),*].join(", ");
-                            let placeholders = [#(#placeholders),*].join(", ");
-                            for entity in entities {
-                                let sql = format!(
-                                    "INSERT INTO {} ({}) VALUES ({})",
-                                    #table_name,
-                                    fields,
-                                    placeholders
-                                );
-                                let mut query = sqlx::query(&sql);
-                                #(
-                                    query = query.bind(&entity.#field_idents);
-                                )*
-                                query.execute(self.get_pool()).await?;
-                            }
-                            Ok(())
-                        }
-                    }
+            fn insert_ignore(&self, entity: &#struct_name) -> impl std::future::Future<Output = Result<bool, Self::Error>> + Send {
+                async move {
+                    let sql = #insert_ignore_sql;
+                    let mut query = sqlx::query(&sql);
+                    #(
+                        query = query.bind(#upsert_binds);
+                    )*
+                    let mut conn = self.connection().await?;
+                    let result = query.execute(&mut *conn).await?;
+                    Ok(result.rows_affected() > 0)
                 }
             }
         }
-    };
 
-    TokenStream::from(expanded)
-}
+        impl<DB> typed_sqlx_client::CrudOpsRef<#primary_key_type, #struct_name>
+            for typed_sqlx_client::SqlTable<#backend, DB, #struct_name>
+        #where_clause
+        {
+            type Error = sqlx::Error;
+            type InsertOutput = #insert_output;
+            type BatchInsertOutput = #batch_insert_output;
 
-fn parse_db_type(attrs: &[syn::Attribute]) -> String {
-    for attr in attrs {
-        if attr.path().is_ident("crud") {
-            let parser = syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated;
-            if let Ok(meta_list) = attr.parse_args_with(parser) {
-                for meta in meta_list {
-                    if let syn::Meta::NameValue(nv) = meta {
-                        if nv.path.is_ident("db") {
-                            if let syn::Expr::Lit(expr_lit) = &nv.value {
-                                if let syn::Lit::Str(litstr) = &expr_lit.lit {
-                                    return litstr.value();
-                                }
-                            }
-                        }
-                    }
+            fn table_name(&self) -> &'static str {
+                #table_name
+            }
+
+            fn qualified_table_name(&self) -> String {
+                match self.schema() {
+                    Some(schema) => format!("{}.{}", schema, #bare_table_name),
+                    None => String::from(#default_qualified_table),
                 }
             }
-        }
-    }
-    "mysql".to_string()
-}
 
-fn get_crud_rename(attrs: &[Attribute]) -> Option<String> {
-    for attr in attrs {
-        if attr.path().is_ident("crud") {
-            let parser = syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated;
-            if let Ok(meta_list) = attr.parse_args_with(parser) {
-                for meta in meta_list {
-                    if let syn::Meta::NameValue(nv) = meta {
-                        if nv.path.is_ident("rename") {
-                            if let syn::Expr::Lit(expr_lit) = &nv.value {
-                                if let syn::Lit::Str(litstr) = &expr_lit.lit {
-                                    return Some(litstr.value());
-                                }
-                            }
-                        }
-                    }
+            fn delete_by_id(&self, id: &#primary_key_type) -> impl std::future::Future<Output = Result<u64, Self::Error>> + Send {
+                async move {
+                    let sql = #delete_sql_expr;
+                    let mut conn = self.connection().await?;
+                    let result = sqlx::query(&sql)
+                        #(.bind(#id_binds))*
+                        .execute(&mut *conn)
+                        .await?;
+                    #not_found_check
+                    Ok(result.rows_affected())
                 }
             }
-        }
-    }
-    None
-}
 
-fn parse_table_name(attrs: &[syn::Attribute], default: &str) -> String {
-    for attr in attrs {
-        if attr.path().is_ident("crud") {
-            let parser = syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated;
-            if let Ok(meta_list) = attr.parse_args_with(parser) {
-                for meta in meta_list {
-                    if let syn::Meta::NameValue(nv) = meta {
-                        if nv.path.is_ident("table") {
-                            if let syn::Expr::Lit(expr_lit) = &nv.value {
-                                if let syn::Lit::Str(litstr) = &expr_lit.lit {
-                                    return litstr.value();
+            fn get_by_id(&self, id: &#primary_key_type) -> impl std::future::Future<Output = Result<Option<#struct_name>, Self::Error>> + Send {
+                async move {
+                    let sql = #select_sql_expr;
+                    let mut conn = self.connection().await?;
+                    let result = sqlx::query_as::<#backend, #struct_name>(&sql)
+                        #(.bind(#id_binds))*
+                        .fetch_optional(&mut *conn)
+                        .await?;
+                    Ok(result)
+                }
+            }
+
+            fn insert(&self, entity: &#struct_name) -> impl std::future::Future<Output = Result<Self::InsertOutput, Self::Error>> + Send {
+                #insert_body
+            }
+
+            fn update_by_id(&self, id: &#primary_key_type, entity: &#struct_name) -> impl std::future::Future<Output = Result<u64, Self::Error>> + Send {
+                #update_body
+            }
+
+            fn insert_returning(&self, entity: &#struct_name) -> impl std::future::Future<Output = Result<#struct_name, Self::Error>> + Send {
+                #insert_returning_body
+            }
+
+            fn update_by_id_returning(&self, id: &#primary_key_type, entity: &#struct_name) -> impl std::future::Future<Output = Result<Option<#struct_name>, Self::Error>> + Send {
+                #update_returning_body
+            }
+
+            fn insert_batch(&self, entities: &[#struct_name]) -> impl std::future::Future<Output = Result<Self::BatchInsertOutput, Self::Error>> + Send {
+                #insert_batch_body
+            }
+
+            fn insert_batch_best_effort(&self, entities: &[#struct_name]) -> impl std::future::Future<Output = Result<typed_sqlx_client::BatchInsertReport<Self::Error>, Self::Error>> + Send {
+                async move {
+                    let fields = #insert_fields;
+                    let mut conn = self.connection().await?;
+                    let mut report = typed_sqlx_client::BatchInsertReport::default();
+                    for (chunk_index, chunk) in entities.chunks(#struct_name::#insert_batch_rows).enumerate() {
+                        let offset = chunk_index * #struct_name::#insert_batch_rows;
+                        let values = #batch_values;
+                        let sql = format!(
+                            "INSERT INTO {} ({}) VALUES {}",
+                            #sql_table,
+                            fields,
+                            values
+                        );
+                        let mut query = sqlx::query(&sql);
+                        for entity in chunk {
+                            #(
+                                query = query.bind(#insert_binds);
+                            )*
+                        }
+                        // Every attempt gets its own (nested) transaction, so a failed statement
+                        // does not abort an enclosing `SqlTx` on PostgreSQL.
+                        let mut attempt = sqlx::Connection::begin(&mut *conn).await?;
+                        if query.execute(&mut *attempt).await.is_ok() {
+                            attempt.commit().await?;
+                            report.inserted += chunk.len();
+                            continue;
+                        }
+                        attempt.rollback().await?;
+                        // The multi-row statement failed as a whole; retry row by row to find the offenders.
+                        let sql = #single_insert_sql;
+                        for (row_index, entity) in chunk.iter().enumerate() {
+                            let mut query = sqlx::query(&sql);
+                            #(
+                                query = query.bind(#insert_binds);
+                            )*
+                            let mut attempt = sqlx::Connection::begin(&mut *conn).await?;
+                            match query.execute(&mut *attempt).await {
+                                Ok(_) => {
+                                    attempt.commit().await?;
+                                    report.inserted += 1;
+                                }
+                                Err(e) => {
+                                    attempt.rollback().await?;
+                                    report.failed.push((offset + row_index, e));
                                 }
                             }
                         }
                     }
+                    Ok(report)
                 }
             }
         }
-    }
-    default.to_string()
+    };
+
+    Ok((patch_struct, impls))
 }
 
-// Helper function to check if field has primary_key attribute
-fn has_primary_key_attr(attrs: &[Attribute]) -> bool {
-    for attr in attrs {
-        if attr.path().is_ident("crud") {
-            if let Meta::List(meta_list) = &attr.meta {
-                let tokens_str = meta_list.tokens.to_string();
-                if tokens_str.contains("primary_key") {
-                    return true;
-                }
+/// Expression turning `sql`, which evaluates to `statement` written against
+/// `default_sql_table`, into a `Cow<str>` for the schema of the `SqlTable` in `self`.
+/// The table is always the first name in a generated statement, so with a schema override
+/// the text around it is kept and the table is `schema_table`, an expression of `schema`.
+fn resolve_schema(
+    schema_table: &TokenStream2,
+    default_sql_table: &str,
+    statement: &str,
+    sql: TokenStream2,
+) -> TokenStream2 {
+    let table_start = statement
+        .find(default_sql_table)
+        .expect("generated statements name their table");
+    let table_end = table_start + default_sql_table.len();
+    quote! {
+        match self.schema() {
+            Some(schema) => {
+                let sql: &str = #sql;
+                std::borrow::Cow::Owned(format!(
+                    "{}{}{}",
+                    &sql[..#table_start],
+                    #schema_table,
+                    &sql[#table_end..],
+                ))
             }
+            None => std::borrow::Cow::Borrowed(#sql),
         }
     }
-    false
+}
+
+/// `a = $1 AND b = $2` condition over the key columns, numbering placeholders from `start`.
+fn key_condition(dialect: Dialect, pk_names: &[String], start: usize) -> String {
+    pk_names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| format!("{} = {}", name, dialect.placeholder(start + i)))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// Whether `ty` is written as `Option<T>`.
+fn is_option(ty: &syn::Type) -> bool {
+    !std::ptr::eq(extract_option_inner_type_deep(ty), ty)
+}
+
+/// `ty` as written in the source, without the token spacing of `TokenStream::to_string`.
+fn type_name(ty: &syn::Type) -> String {
+    quote!(#ty)
+        .to_string()
+        .replace(" :: ", "::")
+        .replace(":: ", "::")
+        .replace(" <", "<")
+        .replace("< ", "<")
+        .replace(" >", ">")
+        .replace(" ,", ",")
+        .replace("& ", "&")
+        .replace("( ", "(")
+        .replace(" )", ")")
+        .replace("[ ", "[")
+        .replace(" ]", "]")
+        .replace(" ;", ";")
 }
 
 fn extract_option_inner_type_deep(ty: &syn::Type) -> &syn::Type {